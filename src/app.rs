@@ -1,9 +1,19 @@
-use crate::index::{discover_and_sort_files, index_files, IndexProgress, IndexState, SessionIndex};
+use crate::archive;
+use crate::config::{Config, ResumeConfig, ResumeTarget};
+use crate::git::{self, Commit};
+use crate::index::{
+    discover_and_sort_files, index_files, index_key, purge_if_ignore_rules_changed, BranchFilter,
+    IndexKey, IndexProgress, IndexState, PageKey, SearchPage, SessionIndex, SortOrder,
+    SubagentFilter,
+};
+use crate::launch;
 use crate::parser;
+use crate::paths;
+use crate::remap::{self, Remaps};
 use crate::session::{SearchResult, Session};
 use anyhow::Result;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use std::time::{Duration, Instant};
@@ -11,6 +21,9 @@ use std::time::{Duration, Instant};
 /// Debounce delay for search (avoid searching on every keystroke during fast typing/paste)
 const SEARCH_DEBOUNCE: Duration = Duration::from_millis(50);
 
+/// Number of sessions fetched per page (more are loaded when scrolling past the bottom)
+const PAGE_SIZE: usize = 50;
+
 /// Messages from the indexing thread
 pub enum IndexMsg {
     Progress { indexed: usize, total: usize },
@@ -26,6 +39,164 @@ pub enum SearchScope {
     Everything,
     /// Search only conversations from a specific folder
     Folder(String),
+    /// Search conversations from a folder and its subfolders
+    Subtree(String),
+    /// Search conversations anywhere in a git repository (given its root folder)
+    Repo(String),
+    /// Search conversations in any worktree or clone of a repository: the root
+    /// folder of the one searched from, and the root folders of all of them
+    Worktrees { root: String, roots: Vec<String> },
+}
+
+/// Kinds of search scopes, from narrowest to widest (the order `/` cycles through)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScopeKind {
+    Folder,
+    Subtree,
+    Repo,
+    Worktrees,
+    Everything,
+}
+
+impl ScopeKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "folder" => Some(ScopeKind::Folder),
+            "subtree" => Some(ScopeKind::Subtree),
+            "repo" => Some(ScopeKind::Repo),
+            "worktrees" => Some(ScopeKind::Worktrees),
+            "everything" => Some(ScopeKind::Everything),
+            _ => None,
+        }
+    }
+
+    /// The next wider kind, wrapping around to the narrowest
+    pub fn next(&self) -> Self {
+        match self {
+            ScopeKind::Folder => ScopeKind::Subtree,
+            ScopeKind::Subtree => ScopeKind::Repo,
+            ScopeKind::Repo => ScopeKind::Worktrees,
+            ScopeKind::Worktrees => ScopeKind::Everything,
+            ScopeKind::Everything => ScopeKind::Folder,
+        }
+    }
+}
+
+impl SearchScope {
+    /// The scope of a kind around a directory, or None for the git scopes outside of
+    /// a git repository. Worktrees and clones are found among `known_cwds` (the
+    /// folders of indexed sessions), by their common git dir or remote.
+    pub fn around(kind: ScopeKind, dir: &str, known_cwds: impl FnOnce() -> Vec<String>) -> Option<Self> {
+        let path = Path::new(dir);
+        let root = || git::repo_root(path).map(|r| r.to_string_lossy().to_string());
+        Some(match kind {
+            ScopeKind::Everything => SearchScope::Everything,
+            ScopeKind::Folder => SearchScope::Folder(dir.to_string()),
+            ScopeKind::Subtree => SearchScope::Subtree(dir.to_string()),
+            ScopeKind::Repo => SearchScope::Repo(root()?),
+            ScopeKind::Worktrees => {
+                let root = root()?;
+                let common_dir = git::common_dir(path);
+                let remote = git::remote_url(path).map(|url| git::normalize_remote(&url));
+
+                let mut roots = vec![root.clone()];
+                for cwd in known_cwds() {
+                    let cwd = Path::new(&cwd);
+                    let Some(other_root) = git::repo_root(cwd) else {
+                        continue;
+                    };
+                    let other_root = other_root.to_string_lossy().to_string();
+                    if roots.contains(&other_root) {
+                        continue;
+                    }
+                    let shared = (common_dir.is_some() && git::common_dir(cwd) == common_dir)
+                        || (remote.is_some()
+                            && git::remote_url(cwd).map(|url| git::normalize_remote(&url)) == remote);
+                    if shared {
+                        roots.push(other_root);
+                    }
+                }
+                roots.sort();
+                SearchScope::Worktrees { root, roots }
+            }
+        })
+    }
+
+    pub fn kind(&self) -> ScopeKind {
+        match self {
+            SearchScope::Everything => ScopeKind::Everything,
+            SearchScope::Folder(_) => ScopeKind::Folder,
+            SearchScope::Subtree(_) => ScopeKind::Subtree,
+            SearchScope::Repo(_) => ScopeKind::Repo,
+            SearchScope::Worktrees { .. } => ScopeKind::Worktrees,
+        }
+    }
+
+    /// Whether a conversation from a working directory is in scope
+    pub fn matches(&self, cwd: &str) -> bool {
+        match self {
+            SearchScope::Everything => true,
+            SearchScope::Folder(dir) => cwd == dir,
+            SearchScope::Subtree(dir) | SearchScope::Repo(dir) => Path::new(cwd).starts_with(dir),
+            SearchScope::Worktrees { roots, .. } => {
+                roots.iter().any(|root| Path::new(cwd).starts_with(root))
+            }
+        }
+    }
+
+    /// The folders whose subtrees the scope covers (for telling apart scopes of
+    /// different kinds that cover the same conversations)
+    fn subtrees(&self) -> Option<Vec<&str>> {
+        match self {
+            SearchScope::Subtree(dir) | SearchScope::Repo(dir) => Some(vec![dir.as_str()]),
+            SearchScope::Worktrees { roots, .. } => Some(roots.iter().map(|r| r.as_str()).collect()),
+            _ => None,
+        }
+    }
+}
+
+/// Find in the previewed session (Ctrl+F)
+#[derive(Debug, Clone, Default)]
+pub struct Find {
+    /// Session being searched (find ends when another one is selected)
+    pub session_id: String,
+    pub query: String,
+    /// Whether the query is being typed (otherwise n/N step between hits)
+    pub editing: bool,
+    /// Messages with matches, as (message index, number of matches)
+    pub hits: Vec<(usize, usize)>,
+    /// Position of the focused match among all matches in the session
+    pub current: usize,
+    /// The session, parsed once when find opens
+    session: Option<Session>,
+}
+
+impl Find {
+    /// Total number of matches in the session
+    pub fn match_count(&self) -> usize {
+        self.hits.iter().map(|(_, count)| count).sum()
+    }
+
+    /// The focused match, as (message index, position among that message's matches)
+    pub fn current_match(&self) -> Option<(usize, usize)> {
+        let mut before = 0;
+        for &(idx, count) in &self.hits {
+            if self.current < before + count {
+                return Some((idx, self.current - before));
+            }
+            before += count;
+        }
+        None
+    }
+}
+
+/// Where to open the selected session, being chosen (Ctrl+O)
+#[derive(Debug, Clone)]
+pub struct TargetPicker {
+    /// Targets that can be opened from here
+    pub targets: Vec<ResumeTarget>,
+    /// Position of the highlighted target in `targets`
+    pub selected: usize,
 }
 
 pub struct App {
@@ -35,6 +206,8 @@ pub struct App {
     pub cursor: usize,
     /// Search results
     pub results: Vec<SearchResult>,
+    /// Where the next page of results starts (None if all are loaded)
+    next_page: Option<PageKey>,
     /// Selected result index
     pub selected: usize,
     /// Results list scroll offset
@@ -63,6 +236,18 @@ pub struct App {
     pub should_resume: Option<Session>,
     /// Session ID to copy (set on Tab)
     pub should_copy: Option<String>,
+    /// Target preselected when opening sessions next to the TUI (Ctrl+O)
+    pub resume_target: ResumeTarget,
+    /// Choosing where to open the selected session, when open
+    pub target_picker: Option<TargetPicker>,
+    /// Templates for the commands that resume sessions
+    resume_config: ResumeConfig,
+    /// Whether to show the commits made during sessions in the preview
+    pub show_commits: bool,
+    /// Commits made during the selected session (shown between its messages in the preview)
+    pub commits: Vec<Commit>,
+    /// Commits made during sessions, by session ID (looked up with git once per session)
+    commit_cache: HashMap<String, Vec<Commit>>,
     /// Index for searching
     index: SessionIndex,
     /// Status message (for indexing progress, etc.)
@@ -75,8 +260,26 @@ pub struct App {
     pub indexing: bool,
     /// Current search scope
     pub search_scope: SearchScope,
+    /// Scopes `/` cycles through (worked out on first use, and after indexing)
+    scope_candidates: Option<Vec<SearchScope>>,
     /// Launch directory (for folder-scoped search)
     pub launch_cwd: String,
+    /// Where imported sessions are kept (to mark results as archived)
+    archive_dir: PathBuf,
+    /// Session whose related sessions are being listed (replaces the query results)
+    pub related_to: Option<Session>,
+    /// Find in the previewed session, when open
+    pub find: Option<Find>,
+    /// Whether to show one result per thread (continuations and forks grouped)
+    pub group_threads: bool,
+    /// Private mode: hide projects flagged private in the ignore rules
+    pub private: bool,
+    /// Thread size of each grouped result, by session ID
+    pub thread_sizes: HashMap<String, usize>,
+    /// Which sessions to show, by whether they're subagent transcripts (Ctrl+T)
+    pub subagent_filter: SubagentFilter,
+    /// Subagent transcripts run by the selected session (nested in its preview)
+    pub subagents: Vec<Session>,
     /// Whether a search is pending (for debouncing)
     search_pending: bool,
     /// When the last input occurred (for debouncing)
@@ -87,19 +290,24 @@ pub struct App {
 
 impl App {
     pub fn new(initial_query: String) -> Result<Self> {
-        // Allow override for testing
-        let cache_dir = std::env::var("RECALL_HOME_OVERRIDE")
-            .map(|h| PathBuf::from(h).join(".cache").join("recall"))
-            .unwrap_or_else(|_| {
-                dirs::cache_dir()
-                    .unwrap_or_else(|| PathBuf::from("."))
-                    .join("recall")
-            });
+        Self::with_key(initial_query, None)
+    }
+
+    /// Create the app with an unlocked index key (without one, an encrypted
+    /// index is unlocked with the key from the environment or keyfile)
+    pub fn with_key(initial_query: String, key: Option<IndexKey>) -> Result<Self> {
+        let cache_dir = paths::cache_dir();
 
         let index_path = cache_dir.join("index");
         let state_path = cache_dir.join("state.json");
 
-        let index = SessionIndex::open_or_create(&index_path)?;
+        let config = Config::load()?;
+        let key = match key {
+            Some(key) => Some(key),
+            None => index_key(&config.encryption, &index_path, None)?,
+        };
+        let mut index = SessionIndex::open_or_create_with_key(&index_path, key.clone())?;
+        index.configure(&config)?;
 
         // Get launch directory (override for tests)
         let launch_cwd = std::env::var("RECALL_CWD_OVERRIDE").unwrap_or_else(|_| {
@@ -108,11 +316,15 @@ impl App {
                 .unwrap_or_default()
         });
 
+        let resume_target = config.resume.target();
+        let resume_config = config.resume.clone();
+        let show_commits = config.git.commits;
+
         // Start background indexing
         let (tx, rx) = mpsc::channel();
         let index_path_clone = index_path.clone();
         thread::spawn(move || {
-            background_index(index_path_clone, state_path, tx);
+            background_index(index_path_clone, state_path, key, &config, tx);
         });
 
         let initial_cursor = initial_query.chars().count();
@@ -120,6 +332,7 @@ impl App {
             query: initial_query,
             cursor: initial_cursor,
             results: Vec::new(),
+            next_page: None,
             selected: 0,
             list_scroll: 0,
             preview_scroll: 0,
@@ -134,13 +347,28 @@ impl App {
             should_quit: false,
             should_resume: None,
             should_copy: None,
+            resume_target,
+            target_picker: None,
+            resume_config,
+            show_commits,
+            commits: Vec::new(),
+            commit_cache: HashMap::new(),
             index,
             status: None,
             total_sessions: 0,
             index_rx: Some(rx),
             indexing: true,
             search_scope: SearchScope::Folder(launch_cwd.clone()),
+            scope_candidates: None,
             launch_cwd,
+            archive_dir: archive::archive_dir(),
+            related_to: None,
+            find: None,
+            group_threads: false,
+            private: false,
+            thread_sizes: HashMap::new(),
+            subagent_filter: SubagentFilter::default(),
+            subagents: Vec::new(),
             search_pending: false,
             last_input: Instant::now(),
             index_error: None,
@@ -194,6 +422,8 @@ impl App {
                     self.total_sessions = total_sessions;
                     self.status = None;
                     self.indexing = false;
+                    // Newly indexed folders may add worktrees to the scopes
+                    self.scope_candidates = None;
                     should_close_rx = true;
                     needs_reload = true;
                     needs_search = true;
@@ -231,19 +461,18 @@ impl App {
         // Remember currently selected session to preserve selection
         let selected_session_id = self.results.get(self.selected).map(|r| r.session.id.clone());
 
-        let mut results = if self.query.is_empty() {
-            self.index.recent(50)?
+        self.thread_sizes.clear();
+        if let Some(ref session) = self.related_to {
+            // Related sessions ignore the query and scope (they're often in other repos)
+            self.results = self.index.related(&session.id, PAGE_SIZE, |s| !self.is_hidden(s))?;
+            self.next_page = None;
         } else {
-            self.index.search(&self.query, 50)?
-        };
-
-        // Filter by scope if searching within a folder
-        if let SearchScope::Folder(ref cwd) = self.search_scope {
-            results.retain(|r| r.session.cwd == *cwd);
+            let page = self.fetch_page(None)?;
+            self.results.clear();
+            self.results = self.group_by_thread(page.results)?;
+            self.next_page = page.next;
         }
 
-        self.results = results;
-
         // Try to preserve selection on the same session
         if let Some(ref id) = selected_session_id {
             if let Some(pos) = self.results.iter().position(|r| &r.session.id == id) {
@@ -263,55 +492,259 @@ impl App {
         Ok(())
     }
 
-    /// Toggle search scope between everything and current folder
-    pub fn toggle_scope(&mut self) {
-        self.search_scope = match self.search_scope {
-            SearchScope::Everything => SearchScope::Folder(self.launch_cwd.clone()),
-            SearchScope::Folder(_) => SearchScope::Everything,
+    /// Fetch a page of results for the current query and scope
+    fn fetch_page(&self, after: Option<&PageKey>) -> Result<SearchPage> {
+        // `branch:<name>` in the query filters instead of searching
+        let (query, branch) = BranchFilter::from_query(&self.query);
+        let query = query.trim();
+        let mut page = if query.is_empty() {
+            self.index.recent_page(after, PAGE_SIZE)?
+        } else {
+            self.index.search_page(query, SortOrder::Relevance, after, PAGE_SIZE)?
+        };
+
+        // Filter by scope if searching within a folder
+        page.results.retain(|r| self.search_scope.matches(&r.session.cwd));
+        page.results.retain(|r| branch.as_ref().is_none_or(|b| b.matches(&r.session)));
+        page.results.retain(|r| !self.is_hidden(&r.session));
+        page.results.retain(|r| self.subagent_filter.matches(&r.session));
+
+        Ok(page)
+    }
+
+    /// Append the next page of results, skipping pages emptied by the scope filter.
+    /// Returns whether any results were added.
+    pub fn load_more(&mut self) -> Result<bool> {
+        let loaded = self.results.len();
+        while let Some(after) = self.next_page.take() {
+            let page = self.fetch_page(Some(&after))?;
+            self.next_page = page.next;
+            let results = self.group_by_thread(page.results)?;
+            self.results.extend(results);
+            if self.results.len() > loaded {
+                break;
+            }
+        }
+        Ok(self.results.len() > loaded)
+    }
+
+    /// Keep only the best-ranked result of each thread (when grouping is on),
+    /// skipping threads already in the results
+    fn group_by_thread(&mut self, results: Vec<SearchResult>) -> Result<Vec<SearchResult>> {
+        if !self.group_threads {
+            return Ok(results);
+        }
+
+        let ids: Vec<&str> = results.iter().map(|r| r.session.id.as_str()).collect();
+        let mut threads = self.index.threads(&ids)?;
+
+        let mut grouped = Vec::new();
+        for result in results {
+            let thread = threads
+                .remove(&result.session.id)
+                .unwrap_or_else(|| vec![result.session.id.clone()]);
+            let in_thread = |r: &SearchResult| thread.contains(&r.session.id);
+            if self.results.iter().any(in_thread) || grouped.iter().any(in_thread) {
+                continue;
+            }
+            self.thread_sizes.insert(result.session.id.clone(), thread.len());
+            grouped.push(result);
+        }
+        Ok(grouped)
+    }
+
+
+    /// Look up the commits made during the selected session, or none if commits
+    /// aren't shown
+    fn load_commits(&mut self) {
+        let selected = self.selected_result().filter(|_| self.show_commits);
+        let Some(mut session) = selected.map(|r| r.session.clone()) else {
+            self.commits = Vec::new();
+            return;
+        };
+        self.commits = self
+            .commit_cache
+            .entry(session.id.clone())
+            .or_insert_with(|| {
+                if let Ok(remaps) = Remaps::load() {
+                    remap::remap_session_cwd(&mut session, &remaps);
+                }
+                git::session_commits(&session).unwrap_or_default()
+            })
+            .clone();
+    }
+
+    /// Show all sessions, then only top-level ones, then only subagents (Ctrl+T)
+    pub fn cycle_subagent_filter(&mut self) {
+        self.subagent_filter = match self.subagent_filter {
+            SubagentFilter::Include => SubagentFilter::Exclude,
+            SubagentFilter::Exclude => SubagentFilter::Only,
+            SubagentFilter::Only => SubagentFilter::Include,
+        };
+        let _ = self.search();
+    }
+
+    /// Toggle grouping results by thread (Ctrl+G)
+    pub fn toggle_thread_grouping(&mut self) {
+        self.group_threads = !self.group_threads;
+        let _ = self.search();
+    }
+
+    /// Select the previous or next session in the selected session's thread (Ctrl+P/Ctrl+N),
+    /// adding it to the results if it isn't there
+    pub fn step_thread(&mut self, forward: bool) {
+        let Some(result) = self.results.get(self.selected) else {
+            return;
+        };
+        let Ok(thread) = self.index.thread(&result.session.id) else {
+            return;
+        };
+        let Some(pos) = thread.iter().position(|s| s.id == result.session.id) else {
+            return;
+        };
+        let target = if forward {
+            thread.get(pos + 1)
+        } else {
+            pos.checked_sub(1).and_then(|p| thread.get(p))
+        };
+        let Some(target) = target else {
+            return;
+        };
+
+        if let Some(existing) = self.results.iter().position(|r| r.session.id == target.id) {
+            self.selected = existing;
+        } else {
+            let insert_at = if forward { self.selected + 1 } else { self.selected };
+            self.results.insert(
+                insert_at,
+                SearchResult {
+                    session: target.clone(),
+                    score: 0.0,
+                    score_breakdown: Default::default(),
+                    matched_message_index: 0,
+                    snippet: target.title.clone(),
+                    match_spans: Vec::new(),
+                    match_fragment: String::new(),
+                },
+            );
+            self.selected = insert_at;
+        }
+        self.update_preview_scroll();
+    }
+
+    /// Widen the search scope to the next kind (folder, subtree, git repository,
+    /// all worktrees, everything, then back to folder), skipping git scopes outside
+    /// a repository and scopes that cover the same folders as the current one
+    pub fn cycle_scope(&mut self) {
+        let current = self.search_scope.clone();
+        let candidates = self.scope_candidates();
+        let next = candidates
+            .iter()
+            .find(|s| {
+                s.kind() > current.kind()
+                    && (s.subtrees().is_none() || s.subtrees() != current.subtrees())
+            })
+            .unwrap_or(&candidates[0])
+            .clone();
+        self.search_scope = next;
+        let _ = self.search();
+    }
+
+    /// The scopes `/` cycles through around the launch directory, narrowest first.
+    /// They're worked out (with a scan of the indexed folders and git lookups) on
+    /// first use, and again after indexing finishes.
+    fn scope_candidates(&mut self) -> &[SearchScope] {
+        if self.scope_candidates.is_none() {
+            let mut candidates: Vec<SearchScope> = Vec::new();
+            let mut kind = ScopeKind::Everything;
+            loop {
+                kind = kind.next();
+                let index = &self.index;
+                let scope = SearchScope::around(kind, &self.launch_cwd, || {
+                    index.known_cwds().unwrap_or_default()
+                });
+                // Skip git scopes outside a repository, and ones covering the same
+                // folders as the narrower scope before them
+                let narrower = candidates.last().and_then(|l| l.subtrees());
+                if let Some(scope) =
+                    scope.filter(|s| s.subtrees().is_none() || s.subtrees() != narrower)
+                {
+                    candidates.push(scope);
+                }
+                if kind == ScopeKind::Everything {
+                    break;
+                }
+            }
+            self.scope_candidates = Some(candidates);
+        }
+        self.scope_candidates.as_deref().unwrap_or_default()
+    }
+
+    /// Turn private mode on or off, hiding projects flagged private
+    pub fn set_private(&mut self, private: bool) {
+        self.private = private;
+        let _ = self.search();
+    }
+
+    /// Whether a session was imported from a bundle (read-only)
+    pub fn is_archived(&self, session: &Session) -> bool {
+        session.file_path.starts_with(&self.archive_dir)
+    }
+
+    /// Whether a session is hidden by private mode
+    fn is_hidden(&self, session: &Session) -> bool {
+        self.private && self.index.ignore_rules().is_private(&session.cwd)
+    }
+
+    /// List sessions related to the selected one (Ctrl+R)
+    pub fn show_related(&mut self) {
+        if let Some(result) = self.results.get(self.selected) {
+            self.related_to = Some(result.session.clone());
+            let _ = self.search();
+        }
+    }
+
+    /// Leave the related sessions list and return to the previous results
+    fn exit_related(&mut self) {
+        let Some(session) = self.related_to.take() else {
+            return;
         };
         let _ = self.search();
+        // Re-select the session we came from
+        if let Some(pos) = self.results.iter().position(|r| r.session.id == session.id) {
+            self.selected = pos;
+            self.list_scroll = pos;
+            self.update_preview_scroll();
+        }
     }
 
     /// Get the folder name for display (last component of path)
     pub fn scope_folder_name(&self) -> Option<&str> {
         match &self.search_scope {
             SearchScope::Everything => None,
-            SearchScope::Folder(path) => {
+            SearchScope::Folder(path)
+            | SearchScope::Subtree(path)
+            | SearchScope::Repo(path)
+            | SearchScope::Worktrees { root: path, .. } => {
                 path.rsplit(std::path::MAIN_SEPARATOR).next()
             }
         }
     }
 
-    /// Get a compact display path for the scope
-    /// - Replaces home dir with ~
-    /// - If short enough, shows full path
-    /// - Otherwise shows ~/.../<dir> or /.../<dir>
+    /// Get a compact display label for the scope: the folder's path, followed by
+    /// `/**` for its subtree, and prefixed with `git:` for its repository
+    /// (`git:~/api +2` with two other worktrees)
     pub fn scope_display_path(&self) -> Option<String> {
         let path = match &self.search_scope {
             SearchScope::Everything => return None,
-            SearchScope::Folder(path) => path.as_str(),
-        };
-
-        // Replace home dir with ~ (HOME on Unix, USERPROFILE on Windows)
-        let home = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .unwrap_or_default();
-        let display_path = if !home.is_empty() && path.starts_with(&home) {
-            format!("~{}", &path[home.len()..])
-        } else {
-            path.to_string()
+            SearchScope::Folder(path) => compact_path(path),
+            SearchScope::Subtree(path) => format!("{}/**", compact_path(path)),
+            SearchScope::Repo(path) => format!("git:{}", compact_path(path)),
+            SearchScope::Worktrees { root, roots } => {
+                format!("git:{} +{}", compact_path(root), roots.len().saturating_sub(1))
+            }
         };
-
-        // If short enough, show full path
-        const MAX_LEN: usize = 25;
-        if display_path.len() <= MAX_LEN {
-            return Some(display_path);
-        }
-
-        // Otherwise show prefix/.../<last_dir>
-        let last_component = path.rsplit(std::path::MAIN_SEPARATOR).next().unwrap_or(path);
-        let prefix = if display_path.starts_with('~') { "~" } else { "" };
-        Some(format!("{}/.../{}", prefix, last_component))
+        Some(path)
     }
 
     /// Handle character input
@@ -345,7 +778,9 @@ impl App {
 
     /// Clear search
     pub fn on_escape(&mut self) {
-        if self.query.is_empty() {
+        if self.related_to.is_some() {
+            self.exit_related();
+        } else if self.query.is_empty() {
             self.should_quit = true;
         } else {
             self.query.clear();
@@ -387,6 +822,9 @@ impl App {
 
     /// Mark that a search is needed (debounced)
     fn mark_search_pending(&mut self) {
+        // Editing the query leaves the related sessions list and find
+        self.related_to = None;
+        self.find = None;
         self.search_pending = true;
         self.last_input = Instant::now();
     }
@@ -415,8 +853,11 @@ impl App {
         }
     }
 
-    /// Move selection down
+    /// Move selection down (loading more results past the last one)
     pub fn on_down(&mut self) {
+        if self.selected + 1 >= self.results.len() {
+            let _ = self.load_more();
+        }
         if !self.results.is_empty() {
             self.selected = (self.selected + 1).min(self.results.len() - 1);
             self.update_preview_scroll();
@@ -433,12 +874,83 @@ impl App {
     /// Handle Enter key - open conversation
     pub fn on_enter(&mut self) {
         if let Some(result) = self.results.get(self.selected) {
+            if self.is_archived(&result.session) {
+                self.status = Some("Archived sessions are read-only".to_string());
+                return;
+            }
             if let Ok(session) = parser::parse_session_file(&result.session.file_path) {
                 self.should_resume = Some(session);
             }
         }
     }
 
+    /// Choose where to open the selected session, next to the TUI (Ctrl+O). The
+    /// configured (or detected) target is preselected.
+    pub fn open_target_picker(&mut self) {
+        if self.selected_result().is_none() {
+            return;
+        }
+        let targets: Vec<ResumeTarget> = ResumeTarget::ALL
+            .into_iter()
+            .filter(|t| t.is_available())
+            .collect();
+        let selected = targets
+            .iter()
+            .position(|&t| t == self.resume_target)
+            .unwrap_or(0);
+        self.target_picker = Some(TargetPicker { targets, selected });
+    }
+
+    /// Close the target picker without opening anything
+    pub fn close_target_picker(&mut self) {
+        self.target_picker = None;
+    }
+
+    /// Move the target picker's selection, wrapping around
+    pub fn step_target(&mut self, forward: bool) {
+        if let Some(picker) = &mut self.target_picker {
+            let len = picker.targets.len();
+            picker.selected = if forward {
+                (picker.selected + 1) % len
+            } else {
+                (picker.selected + len - 1) % len
+            };
+        }
+    }
+
+    /// Open the selected session in the target picked (by its number, or else the
+    /// highlighted one), and keep the TUI
+    pub fn confirm_target(&mut self, number: Option<usize>) {
+        let Some(picker) = self.target_picker.take() else {
+            return;
+        };
+        let index = number.map_or(picker.selected, |n| n.wrapping_sub(1));
+        if let Some(&target) = picker.targets.get(index) {
+            self.launch_selected(target);
+        }
+    }
+
+    /// Open the selected session in a new pane or window, and keep the TUI
+    fn launch_selected(&mut self, target: ResumeTarget) {
+        let Some(result) = self.results.get(self.selected) else {
+            return;
+        };
+        let launched = parser::parse_session_file(&result.session.file_path).and_then(|mut session| {
+            if !remap::remap_session_cwd(&mut session, &Remaps::load()?) {
+                anyhow::bail!("{} no longer exists • Enter to choose where it moved", session.cwd);
+            }
+            launch::launch(&session, target, true, &self.resume_config)
+        });
+        self.status = Some(match launched {
+            Ok(()) if target == ResumeTarget::Screen => format!(
+                "Resumed in screen • screen -r {}",
+                launch::screen_name(&result.session)
+            ),
+            Ok(()) => format!("Resumed in {}", target.display_name()),
+            Err(e) => format!("Couldn't resume: {}", e),
+        });
+    }
+
     /// Update preview scroll to show the matched message
     fn update_preview_scroll(&mut self) {
         // Signal that we need to auto-scroll to the matched message
@@ -449,6 +961,113 @@ impl App {
         // Reset focus and expansions when switching sessions
         self.focused_message = None;
         self.expanded_messages.clear();
+        self.subagents = match self.selected_result() {
+            Some(result) => self.index.subagents(&result.session.id).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        self.load_commits();
+        // Find stays open while its session is selected (results are refreshed while indexing)
+        let selected_id = self.selected_result().map(|r| r.session.id.clone());
+        if self.find.as_ref().is_some_and(|f| Some(&f.session_id) != selected_id.as_ref()) {
+            self.find = None;
+        }
+        self.focus_find_hit();
+    }
+
+    /// Open find in the previewed session (Ctrl+F), starting from the search query's
+    /// words, or go back to editing the find query
+    pub fn open_find(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.editing = true;
+            return;
+        }
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let (query, _) = BranchFilter::from_query(&self.query);
+        self.find = Some(Find {
+            session_id: result.session.id.clone(),
+            query: query.split_whitespace().collect::<Vec<_>>().join(" "),
+            editing: true,
+            session: parser::parse_session_file(&result.session.file_path).ok(),
+            ..Default::default()
+        });
+        self.update_find();
+    }
+
+    /// Close find
+    pub fn close_find(&mut self) {
+        self.find = None;
+    }
+
+    /// Stop editing the find query (Enter), leaving n/N to step between hits
+    pub fn confirm_find(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.editing = false;
+        }
+    }
+
+    /// Type into the find query
+    pub fn on_find_char(&mut self, c: char) {
+        if let Some(find) = &mut self.find {
+            find.query.push(c);
+            self.update_find();
+        }
+    }
+
+    /// Delete the last character of the find query
+    pub fn on_find_backspace(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.query.pop();
+            self.update_find();
+        }
+    }
+
+    /// Focus the next (or previous) match, wrapping around
+    pub fn step_find(&mut self, forward: bool) {
+        let Some(find) = &mut self.find else {
+            return;
+        };
+        let len = find.match_count();
+        if len == 0 {
+            return;
+        }
+        find.current = if forward { (find.current + 1) % len } else { (find.current + len - 1) % len };
+        self.focus_find_hit();
+    }
+
+    /// Search the previewed session for the find query, and focus the first match
+    /// from the focused message on
+    fn update_find(&mut self) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let focused = self.focused_message.unwrap_or(result.matched_message_index);
+        let Some(find) = &mut self.find else {
+            return;
+        };
+        find.hits = find
+            .session
+            .as_ref()
+            .map(|session| session.find_messages(&find.query))
+            .unwrap_or_default();
+        // Matches in the messages before the focused one come first
+        let before = find.hits.iter().take_while(|(idx, _)| *idx < focused);
+        find.current = before.map(|(_, count)| count).sum();
+        if find.current >= find.match_count() {
+            find.current = 0;
+        }
+        self.focus_find_hit();
+    }
+
+    /// Focus and expand the message with the current find match, and scroll to it
+    fn focus_find_hit(&mut self) {
+        let Some((idx, _)) = self.find.as_ref().and_then(Find::current_match) else {
+            return;
+        };
+        self.focused_message = Some(idx);
+        self.expanded_messages.insert(idx);
+        self.pending_auto_scroll = true;
     }
 
     /// Scroll preview up
@@ -540,15 +1159,25 @@ impl App {
 }
 
 /// Background indexing function
-fn background_index(index_path: PathBuf, state_path: PathBuf, tx: Sender<IndexMsg>) {
-    let index = match SessionIndex::open_or_create(&index_path) {
+fn background_index(
+    index_path: PathBuf,
+    state_path: PathBuf,
+    key: Option<IndexKey>,
+    config: &Config,
+    tx: Sender<IndexMsg>,
+) {
+    let mut index = match SessionIndex::open_or_create_with_key(&index_path, key) {
         Ok(idx) => idx,
         Err(e) => {
             let _ = tx.send(IndexMsg::Error(format!("Failed to open index: {}", e)));
             return;
         }
     };
-    let mut state = match IndexState::load(&state_path) {
+    if let Err(e) = index.configure(config) {
+        let _ = tx.send(IndexMsg::Error(format!("Failed to load config: {}", e)));
+        return;
+    }
+    let mut state = match IndexState::load(&state_path, index.key()) {
         Ok(s) => s,
         Err(e) => {
             let _ = tx.send(IndexMsg::Error(format!("Failed to load index state: {}", e)));
@@ -556,8 +1185,16 @@ fn background_index(index_path: PathBuf, state_path: PathBuf, tx: Sender<IndexMs
         }
     };
 
+    let purged = match purge_if_ignore_rules_changed(&index, &mut state) {
+        Ok(purged) => purged,
+        Err(e) => {
+            let _ = tx.send(IndexMsg::Error(format!("Failed to apply ignore rules: {}", e)));
+            return;
+        }
+    };
+
     // Discover and sort files by mtime (most recent first)
-    let files = discover_and_sort_files();
+    let files = discover_and_sort_files(index.ignore_rules());
 
     let files_to_index: Vec<_> = files
         .iter()
@@ -566,6 +1203,9 @@ fn background_index(index_path: PathBuf, state_path: PathBuf, tx: Sender<IndexMs
         .collect();
 
     if files_to_index.is_empty() {
+        if purged {
+            let _ = state.save(&state_path, index.key());
+        }
         let _ = tx.send(IndexMsg::Done {
             total_sessions: files.len(),
         });
@@ -609,13 +1249,40 @@ fn background_index(index_path: PathBuf, state_path: PathBuf, tx: Sender<IndexMs
         return;
     }
 
-    let _ = state.save(&state_path);
+    let _ = state.save(&state_path, index.key());
 
     let _ = tx.send(IndexMsg::Done {
         total_sessions: files.len(),
     });
 }
 
+/// Get a compact display path
+/// - Replaces home dir with ~
+/// - If short enough, shows full path
+/// - Otherwise shows ~/.../<dir> or /.../<dir>
+fn compact_path(path: &str) -> String {
+    // Replace home dir with ~ (HOME on Unix, USERPROFILE on Windows)
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_default();
+    let display_path = if !home.is_empty() && path.starts_with(&home) {
+        format!("~{}", &path[home.len()..])
+    } else {
+        path.to_string()
+    };
+
+    // If short enough, show full path
+    const MAX_LEN: usize = 25;
+    if display_path.len() <= MAX_LEN {
+        return display_path;
+    }
+
+    // Otherwise show prefix/.../<last_dir>
+    let last_component = path.rsplit(std::path::MAIN_SEPARATOR).next().unwrap_or(path);
+    let prefix = if display_path.starts_with('~') { "~" } else { "" };
+    format!("{}/.../{}", prefix, last_component)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -632,6 +1299,7 @@ mod tests {
             query: String::new(),
             cursor: 0,
             results: Vec::new(),
+            next_page: None,
             selected: 0,
             list_scroll: 0,
             preview_scroll: 0,
@@ -646,19 +1314,56 @@ mod tests {
             should_quit: false,
             should_resume: None,
             should_copy: None,
+            resume_target: ResumeTarget::Screen,
+            target_picker: None,
+            resume_config: ResumeConfig::default(),
+            show_commits: false,
+            commits: Vec::new(),
+            commit_cache: HashMap::new(),
             index: SessionIndex::open_or_create(&index_path).unwrap(),
             status: None,
             total_sessions: 0,
             index_rx: None,
             indexing: false,
             search_scope: SearchScope::Everything,
+            scope_candidates: None,
             launch_cwd: String::new(),
+            archive_dir: archive::archive_dir(),
+            related_to: None,
+            find: None,
+            group_threads: false,
+            private: false,
+            thread_sizes: HashMap::new(),
+            subagent_filter: SubagentFilter::default(),
+            subagents: Vec::new(),
             search_pending: false,
             last_input: Instant::now(),
             index_error: None,
         }
     }
 
+    #[test]
+    fn test_scope_matches() {
+        let subtree = SearchScope::Subtree("/work/api".to_string());
+        assert!(subtree.matches("/work/api"));
+        assert!(subtree.matches("/work/api/src"));
+        assert!(!subtree.matches("/work/api-v2"));
+        assert!(!SearchScope::Folder("/work/api".to_string()).matches("/work/api/src"));
+
+        let worktrees = SearchScope::Worktrees {
+            root: "/work/api".to_string(),
+            roots: vec!["/work/api".to_string(), "/work/api-fix".to_string()],
+        };
+        assert!(worktrees.matches("/work/api-fix/src"));
+        assert!(!worktrees.matches("/work/web"));
+
+        let mut app = test_app();
+        app.search_scope = worktrees;
+        assert_eq!(app.scope_display_path().as_deref(), Some("git:/work/api +1"));
+        app.search_scope = subtree;
+        assert_eq!(app.scope_display_path().as_deref(), Some("/work/api/**"));
+    }
+
     // ==================== focus_prev_message tests ====================
 
     #[test]
@@ -891,4 +1596,91 @@ mod tests {
 
         assert!(app.pending_auto_scroll);
     }
+
+    #[test]
+    fn test_cycle_subagent_filter() {
+        let mut app = test_app();
+        assert_eq!(app.subagent_filter, SubagentFilter::Include);
+        app.cycle_subagent_filter();
+        assert_eq!(app.subagent_filter, SubagentFilter::Exclude);
+        app.cycle_subagent_filter();
+        assert_eq!(app.subagent_filter, SubagentFilter::Only);
+        app.cycle_subagent_filter();
+        assert_eq!(app.subagent_filter, SubagentFilter::Include);
+    }
+
+    #[test]
+    fn test_target_picker() {
+        let mut app = test_app();
+        // Nothing to open without a selection
+        app.open_target_picker();
+        assert!(app.target_picker.is_none());
+
+        app.results.push(SearchResult {
+            session: Session::test("a"),
+            score: 0.0,
+            score_breakdown: Default::default(),
+            matched_message_index: 0,
+            snippet: String::new(),
+            match_spans: Vec::new(),
+            match_fragment: String::new(),
+        });
+        app.open_target_picker();
+        let picker = app.target_picker.clone().unwrap();
+        // Screen is always available, and preselected as the configured target
+        assert_eq!(picker.targets.last(), Some(&ResumeTarget::Screen));
+        assert_eq!(picker.targets[picker.selected], ResumeTarget::Screen);
+
+        app.step_target(true);
+        assert_eq!(app.target_picker.as_ref().unwrap().selected, 0);
+        app.step_target(false);
+        assert_eq!(app.target_picker.as_ref().unwrap().selected, picker.selected);
+
+        app.close_target_picker();
+        assert!(app.target_picker.is_none());
+    }
+
+    // ==================== Thread grouping tests ====================
+
+    #[test]
+    fn test_group_by_thread_keeps_best_of_each_thread() {
+        let mut app = test_app();
+        app.group_threads = true;
+
+        // a <- b <- c is one thread; d is a subagent of a, e stands alone
+        let session = |id: &str| Session::test(id).with_cwd("/work").with_title(id);
+        let sessions = [
+            session("a"),
+            session("b").with_parent("a", false),
+            session("c").with_parent("b", false),
+            session("d").with_parent("a", true),
+            session("e"),
+        ];
+        let mut writer = app.index.writer().unwrap();
+        for session in &sessions {
+            app.index.index_session(&mut writer, session).unwrap();
+        }
+        writer.commit().unwrap();
+        app.index.reload().unwrap();
+
+        let results = ["c", "d", "a", "e"]
+            .iter()
+            .map(|id| SearchResult {
+                session: sessions.iter().find(|s| s.id == *id).unwrap().clone(),
+                score: 0.0,
+                score_breakdown: Default::default(),
+                matched_message_index: 0,
+                snippet: String::new(),
+                match_spans: Vec::new(),
+                match_fragment: String::new(),
+            })
+            .collect();
+        let grouped = app.group_by_thread(results).unwrap();
+
+        let ids: Vec<&str> = grouped.iter().map(|r| r.session.id.as_str()).collect();
+        assert_eq!(ids, ["c", "d", "e"]);
+        assert_eq!(app.thread_sizes["c"], 3);
+        assert_eq!(app.thread_sizes["d"], 1);
+        assert_eq!(app.thread_sizes["e"], 1);
+    }
 }
@@ -0,0 +1,325 @@
+//! Export bundles (a directory or tar of session transcripts plus a manifest) and
+//! importing them as read-only archived sessions
+
+use crate::paths;
+use crate::session::{ReadOutput, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the manifest at the root of a bundle
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Bundle layout version written to manifests
+const MANIFEST_VERSION: u32 = 1;
+
+/// Get the directory imported sessions are kept in
+pub fn archive_dir() -> PathBuf {
+    paths::data_dir().join("archive")
+}
+
+/// Whether a session file was imported from a bundle
+pub fn is_archived(path: &Path) -> bool {
+    path.starts_with(archive_dir())
+}
+
+/// Fail for archived sessions, which are read-only and can't be resumed
+pub fn ensure_resumable(session: &Session) -> Result<()> {
+    if session.is_archived() {
+        anyhow::bail!("Session {} is archived (read-only) and can't be resumed", session.id);
+    }
+    Ok(())
+}
+
+/// Index of a bundle's transcripts
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Transcript format (md, html, txt or json); only json bundles can be imported
+    pub format: String,
+    pub sessions: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn new(format: &str) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            created_at: Utc::now(),
+            format: format.to_string(),
+            sessions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub session_id: String,
+    pub title: String,
+    pub source: SessionSource,
+    pub cwd: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub start_timestamp: DateTime<Utc>,
+    /// Transcript file, relative to the bundle root
+    pub path: String,
+}
+
+impl ManifestEntry {
+    /// Describe a session whose transcript is stored with the given extension
+    pub fn new(session: &Session, extension: &str) -> Self {
+        let file_name: String = session
+            .id
+            .chars()
+            .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+            .collect();
+        Self {
+            session_id: session.id.clone(),
+            title: session.title.clone(),
+            source: session.source,
+            cwd: session.cwd.clone(),
+            git_branch: session.git_branch.clone(),
+            timestamp: session.timestamp,
+            start_timestamp: session.start_timestamp,
+            path: format!("sessions/{}/{}.{}", session.source.as_str(), file_name, extension),
+        }
+    }
+}
+
+/// Destination of an export bundle: a directory, or a tar archive for `.tar` paths
+pub enum BundleWriter {
+    Dir(PathBuf),
+    Tar(tar::Builder<std::fs::File>),
+}
+
+impl BundleWriter {
+    pub fn create(path: &Path) -> Result<Self> {
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip")) {
+            anyhow::bail!("Zip bundles aren't supported: write a directory or a .tar file");
+        }
+        if is_tar(path) {
+            let file = std::fs::File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            return Ok(BundleWriter::Tar(tar::Builder::new(file)));
+        }
+        if path.exists() && std::fs::read_dir(path)?.next().is_some() {
+            anyhow::bail!("{} already exists and isn't empty", path.display());
+        }
+        std::fs::create_dir_all(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(BundleWriter::Dir(path.to_path_buf()))
+    }
+
+    /// Add a file at a path relative to the bundle root
+    pub fn add(&mut self, path: &str, data: &[u8]) -> Result<()> {
+        match self {
+            BundleWriter::Dir(root) => {
+                let dest = root.join(path);
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&dest, data)
+                    .with_context(|| format!("Failed to write {}", dest.display()))
+            }
+            BundleWriter::Tar(builder) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(Utc::now().timestamp().max(0) as u64);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, path, data)
+                    .context("Failed to write to the tar archive")
+            }
+        }
+    }
+
+    /// Write the manifest and finish the bundle
+    pub fn finish(mut self, manifest: &Manifest) -> Result<()> {
+        self.add(MANIFEST_FILE, &serde_json::to_vec_pretty(manifest)?)?;
+        if let BundleWriter::Tar(builder) = self {
+            builder.into_inner()?;
+        }
+        Ok(())
+    }
+}
+
+fn is_tar(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tar"))
+}
+
+/// Read a bundle (directory or `.tar`): its manifest and the files it lists
+pub fn read_bundle(path: &Path) -> Result<(Manifest, HashMap<String, Vec<u8>>)> {
+    let mut files = HashMap::new();
+    if path.is_dir() {
+        let manifest = read_manifest(
+            &std::fs::read(path.join(MANIFEST_FILE))
+                .with_context(|| format!("No {} in {}", MANIFEST_FILE, path.display()))?,
+        )?;
+        for entry in &manifest.sessions {
+            check_bundle_path(&entry.path)?;
+            let data = std::fs::read(path.join(&entry.path))
+                .with_context(|| format!("Bundle is missing {}", entry.path))?;
+            files.insert(entry.path.clone(), data);
+        }
+        return Ok((manifest, files));
+    }
+
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = tar::Archive::new(file);
+    for entry in archive.entries().context("Not a tar archive")? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().trim_start_matches("./").to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        files.insert(name, data);
+    }
+    let manifest = read_manifest(
+        &files
+            .remove(MANIFEST_FILE)
+            .with_context(|| format!("No {} in {}", MANIFEST_FILE, path.display()))?,
+    )?;
+    Ok((manifest, files))
+}
+
+fn read_manifest(data: &[u8]) -> Result<Manifest> {
+    let manifest: Manifest = serde_json::from_slice(data).context("Invalid bundle manifest")?;
+    if manifest.version > MANIFEST_VERSION {
+        anyhow::bail!(
+            "Bundle version {} is newer than this recall supports ({})",
+            manifest.version,
+            MANIFEST_VERSION
+        );
+    }
+    Ok(manifest)
+}
+
+/// Refuse paths that would escape the bundle (or the archive, on import)
+fn check_bundle_path(path: &str) -> Result<()> {
+    if path.is_empty() || !Path::new(path).components().all(|c| matches!(c, Component::Normal(_))) {
+        anyhow::bail!("Invalid path in bundle manifest: {:?}", path);
+    }
+    Ok(())
+}
+
+/// Result of `recall import`
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    /// Where the imported transcripts were stored
+    pub archive: PathBuf,
+    pub imported: Vec<String>,
+    /// Sessions that were already indexed
+    pub skipped: Vec<String>,
+}
+
+/// Copy a JSON bundle's transcripts into the archive, under a directory named after
+/// the bundle. Sessions for which `exists` returns true are skipped.
+pub fn import_bundle(path: &Path, exists: impl Fn(&str) -> Result<bool>) -> Result<ImportSummary> {
+    let (manifest, files) = read_bundle(path)?;
+    if manifest.format != "json" {
+        anyhow::bail!(
+            "Only JSON bundles can be imported (this one is {}). Export with --format json",
+            manifest.format
+        );
+    }
+
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "bundle".to_string());
+    let dest = archive_dir().join(name);
+    let mut summary = ImportSummary {
+        archive: dest.clone(),
+        imported: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for entry in &manifest.sessions {
+        check_bundle_path(&entry.path)?;
+        let data = files
+            .get(&entry.path)
+            .with_context(|| format!("Bundle is missing {}", entry.path))?;
+        // Make sure it reads back before it's indexed
+        serde_json::from_slice::<ReadOutput>(data)
+            .with_context(|| format!("Invalid transcript {}", entry.path))?;
+
+        if exists(&entry.session_id)? {
+            summary.skipped.push(entry.session_id.clone());
+            continue;
+        }
+        let target = dest.join(&entry.path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, data)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+        summary.imported.push(entry.session_id.clone());
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ResumeConfig;
+    use crate::session::{Message, Role};
+
+    fn session(id: &str) -> Session {
+        Session::test(id)
+            .with_source(SessionSource::CodexCli)
+            .with_cwd("/work/app")
+            .with_branch("main")
+            .with_title("Hello")
+            .with_messages(vec![Message::text(Role::User, "hello", Default::default())])
+    }
+
+    fn write_bundle(path: &Path) {
+        let mut writer = BundleWriter::create(path).unwrap();
+        let mut manifest = Manifest::new("json");
+        for id in ["s1", "s2"] {
+            let session = session(id);
+            let entry = ManifestEntry::new(&session, "json");
+            writer
+                .add(&entry.path, &serde_json::to_vec(&session.to_read_output(&ResumeConfig::default()).unwrap()).unwrap())
+                .unwrap();
+            manifest.sessions.push(entry);
+        }
+        writer.finish(&manifest).unwrap();
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        for path in [temp.path().join("out"), temp.path().join("out.tar")] {
+            write_bundle(&path);
+            let (manifest, files) = read_bundle(&path).unwrap();
+            assert_eq!(manifest.format, "json");
+            assert_eq!(manifest.sessions.len(), 2);
+            assert_eq!(manifest.sessions[0].path, "sessions/codex/s1.json");
+            let transcript: ReadOutput =
+                serde_json::from_slice(&files["sessions/codex/s2.json"]).unwrap();
+            assert_eq!(transcript.session_id, "s2");
+            assert_eq!(transcript.git_branch.as_deref(), Some("main"));
+            assert_eq!(transcript.messages[0].content, "hello");
+        }
+    }
+
+    #[test]
+    fn test_refuses_non_empty_dirs_and_escaping_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("file"), "x").unwrap();
+        assert!(BundleWriter::create(temp.path()).is_err());
+        assert!(BundleWriter::create(&temp.path().join("out.zip")).is_err());
+        assert!(check_bundle_path("sessions/codex/a.json").is_ok());
+        assert!(check_bundle_path("../a.json").is_err());
+        assert!(check_bundle_path("/etc/passwd").is_err());
+    }
+}
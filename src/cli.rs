@@ -1,15 +1,37 @@
 //! CLI subcommands for non-interactive mode (JSON output for agents)
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
 use recall::{
-    index::{ensure_index_fresh, SessionIndex},
+    app::{ScopeKind, SearchScope},
+    archive::{self, BundleWriter, Manifest, ManifestEntry},
+    config::{Config, RankingConfig, ResumeConfig},
+    export::{self, ExportFormat},
+    handoff,
+    git,
+    index::{ensure_index_fresh, BranchFilter, PageKey, SearchPage, SessionIndex, SortOrder, SubagentFilter},
     parser,
-    session::{ListOutput, Message, SearchOutput, SearchResultOutput, SessionSource},
+    redact::Redactor,
+    remap::{self, Remaps},
+    session::{
+        BlameOutput, HandoffOutput, ListOutput, Message, RelatedOutput, RelatedResultOutput, SearchOutput, SearchResult,
+        SearchResultOutput, Session, SessionSource, ThreadMessage, ThreadOutput,
+    },
 };
 
+use std::path::Path;
+
 const DEFAULT_MESSAGES_PER_SESSION: usize = 5;
 
+/// Sessions fetched from the index at a time by `export --all`
+const EXPORT_BATCH: usize = 200;
+
+/// Candidates listed when a session ID prefix is ambiguous
+const MAX_AMBIGUOUS_CANDIDATES: usize = 10;
+
+/// Sessions considered by `recall blame`
+const MAX_BLAME_CANDIDATES: usize = 10;
+
 /// Run the search subcommand
 #[allow(clippy::too_many_arguments)]
 pub fn run_search(
@@ -20,21 +42,54 @@ pub fn run_search(
     context: usize,
     since: Option<String>,
     until: Option<String>,
-    cwd: Option<String>,
+    by_start: bool,
+    scope: Option<SearchScope>,
+    branch: Option<BranchFilter>,
+    subagents: SubagentFilter,
+    offset: usize,
+    page_token: Option<String>,
+    sort: SortOrder,
+    ranking: RankingConfig,
+    explain: bool,
+    private: bool,
 ) -> Result<()> {
-    let index = SessionIndex::open_default()?;
+    let mut index = SessionIndex::open_default()?;
+    index.set_ranking(ranking);
     ensure_index_fresh(&index)?;
+    let redactor = load_redactor()?;
+    let resume = Config::load()?.resume;
 
     // Parse time filters
     let since_dt = since.as_ref().map(|s| parse_time(s)).transpose()?;
     let until_dt = until.as_ref().map(|s| parse_time(s)).transpose()?;
 
+    // A `branch:` term in the query filters like --branch
+    let full_query = query;
+    let (query, query_branch) = BranchFilter::from_query(full_query);
+    let query = query.as_str();
+    let branch = query_branch.or(branch);
+
     // If searching within a specific session, handle separately
     if let Some(sid) = session_id {
-        return search_in_session(&index, query, &sid, context);
+        return search_in_session(&index, &redactor, &resume, query, &sid, context);
     }
 
-    let results = index.search(query, limit * 2)?; // Get more to filter
+    let start = page_token.as_deref().map(parse_page_token).transpose()?;
+    let (results, next) = collect_page(
+        |after, limit| index.search_page(query, sort, after, limit),
+        |r| {
+            source.is_none_or(|s| r.session.source == s)
+                && since_dt.is_none_or(|t| filter_time(r, by_start) >= t)
+                && until_dt.is_none_or(|t| filter_time(r, by_start) <= t)
+                && scope.as_ref().is_none_or(|s| s.matches(&r.session.cwd))
+                && branch.as_ref().is_none_or(|b| b.matches(&r.session))
+                && subagents.matches(&r.session)
+                && !(private && index.ignore_rules().is_private(&r.session.cwd))
+        },
+        start,
+        offset,
+        limit,
+    )?;
 
     // Pre-compute query terms once (not per-session)
     let query_lower = query.to_lowercase();
@@ -42,21 +97,16 @@ pub fn run_search(
 
     // Convert to output format
     let output = SearchOutput {
-        query: query.to_string(),
+        query: full_query.to_string(),
         results: results
             .into_iter()
-            // Filter by source
-            .filter(|r| source.is_none_or(|s| r.session.source == s))
-            // Filter by time
-            .filter(|r| since_dt.is_none_or(|t| r.session.timestamp >= t))
-            .filter(|r| until_dt.is_none_or(|t| r.session.timestamp <= t))
-            // Filter by working directory
-            .filter(|r| cwd.as_ref().is_none_or(|c| r.session.cwd == *c))
-            .take(limit)
             .map(|r| {
                 // Load full session to get messages
-                let session = parser::parse_session_file(&r.session.file_path)
+                let mut session = parser::parse_session_file(&r.session.file_path)
                     .unwrap_or(r.session.clone());
+                session.strip_reasoning();
+                session.strip_tools();
+                redactor.redact_session(&mut session);
 
                 // Filter and score messages in one pass (avoids repeated to_lowercase in sort)
                 let mut scored_messages: Vec<(usize, usize, &Message)> = session
@@ -98,22 +148,25 @@ pub fn run_search(
                         .collect()
                 };
 
-                let (cmd, args) = r.session.resume_command();
-                let resume_command = std::iter::once(cmd)
-                    .chain(args)
-                    .collect::<Vec<_>>()
-                    .join(" ");
+                let resume_command = r.session.resume_command_line(&resume)?;
 
-                SearchResultOutput {
+                Ok(SearchResultOutput {
                     session_id: r.session.id,
+                    title: r.session.title,
                     source: r.session.source,
                     cwd: r.session.cwd,
                     timestamp: r.session.timestamp,
+                    start_timestamp: r.session.start_timestamp,
+                    active_duration_secs: r.session.active_duration.num_seconds(),
+                    parent_id: r.session.parent_id,
+                    is_subagent: r.session.is_subagent,
                     relevant_messages,
                     resume_command,
-                }
+                    explain: explain.then_some(r.score_breakdown),
+                })
             })
-            .collect(),
+            .collect::<Result<_>>()?,
+        next_page_token: next.map(|key| key.to_token()),
     };
 
     println!("{}", serde_json::to_string_pretty(&output)?);
@@ -123,6 +176,8 @@ pub fn run_search(
 /// Search within a specific session (returns all matches)
 fn search_in_session(
     index: &SessionIndex,
+    redactor: &Redactor,
+    resume: &ResumeConfig,
     query: &str,
     session_id: &str,
     context: usize,
@@ -131,28 +186,16 @@ fn search_in_session(
         .get_by_id(session_id)?
         .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
 
-    let session = parser::parse_session_file(&file_path)?;
-
-    let query_lower = query.to_lowercase();
-    let query_terms: Vec<&str> = query_lower.split_whitespace().collect();
+    let mut session = parser::parse_session_file(&file_path)?;
+    session.strip_reasoning();
+    session.strip_tools();
+    redactor.redact_session(&mut session);
 
-    // Filter and score messages in one pass (avoids repeated to_lowercase in sort)
+    // Score messages by their number of matches
     let mut scored_messages: Vec<(usize, usize, &Message)> = session
-        .messages
-        .iter()
-        .enumerate()
-        .filter_map(|(idx, m)| {
-            let content_lower = m.content.to_lowercase();
-            let score: usize = query_terms
-                .iter()
-                .map(|t| content_lower.matches(t).count())
-                .sum();
-            if score > 0 {
-                Some((idx, score, m))
-            } else {
-                None
-            }
-        })
+        .find_messages(query)
+        .into_iter()
+        .map(|(idx, score)| (idx, score, &session.messages[idx]))
         .collect();
 
     // Sort by pre-computed score (higher first), then recency (higher index first)
@@ -174,22 +217,25 @@ fn search_in_session(
             .collect()
     };
 
-    let (cmd, args) = session.resume_command();
-    let resume_command = std::iter::once(cmd)
-        .chain(args)
-        .collect::<Vec<_>>()
-        .join(" ");
+    let resume_command = session.resume_command_line(resume)?;
 
     let output = SearchOutput {
         query: query.to_string(),
         results: vec![SearchResultOutput {
             session_id: session.id,
+            title: session.title,
             source: session.source,
             cwd: session.cwd,
             timestamp: session.timestamp,
+            start_timestamp: session.start_timestamp,
+            active_duration_secs: session.active_duration.num_seconds(),
+            parent_id: session.parent_id,
+            is_subagent: session.is_subagent,
             relevant_messages,
             resume_command,
+            explain: None,
         }],
+        next_page_token: None,
     };
 
     println!("{}", serde_json::to_string_pretty(&output)?);
@@ -218,46 +264,155 @@ fn collect_with_context(
         .collect()
 }
 
+/// Collect up to `limit` results passing `keep`, skipping the first `skip` of them.
+/// Pages are fetched from the index after the session at `start`; also returns the
+/// key to continue after, if more results remain.
+fn collect_page(
+    fetch: impl Fn(Option<&PageKey>, usize) -> Result<SearchPage>,
+    keep: impl Fn(&SearchResult) -> bool,
+    start: Option<PageKey>,
+    skip: usize,
+    limit: usize,
+) -> Result<(Vec<SearchResult>, Option<PageKey>)> {
+    // Fetch more than needed per page, since filters may drop some results
+    let batch = limit.saturating_mul(2).max(20);
+    let mut results = Vec::new();
+    let mut skipped = 0;
+    let mut after = start;
+    let mut last_key = None;
+
+    loop {
+        let page = fetch(after.as_ref(), batch)?;
+        for (result, key) in page.results.into_iter().zip(page.keys) {
+            if !keep(&result) {
+                continue;
+            }
+            if skipped < skip {
+                skipped += 1;
+                continue;
+            }
+            if results.len() == limit {
+                // There's at least one more result: the next page starts after the last one
+                return Ok((results, last_key));
+            }
+            results.push(result);
+            last_key = Some(key);
+        }
+        match page.next {
+            Some(next) => after = Some(next),
+            None => return Ok((results, None)),
+        }
+    }
+}
+
+/// Get the time that `--since`/`--until` apply to (start time with `--by-start`)
+fn filter_time(result: &SearchResult, by_start: bool) -> DateTime<Utc> {
+    if by_start {
+        result.session.start_timestamp
+    } else {
+        result.session.timestamp
+    }
+}
+
+/// Parse a `--page-token` from a previous `next_page_token`
+fn parse_page_token(token: &str) -> Result<PageKey> {
+    PageKey::parse_token(token).ok_or_else(|| anyhow::anyhow!("Invalid page token: {}", token))
+}
+
 /// Run the list subcommand
+#[allow(clippy::too_many_arguments)]
 pub fn run_list(
     limit: usize,
     source: Option<SessionSource>,
     since: Option<String>,
     until: Option<String>,
-    cwd: Option<String>,
+    by_start: bool,
+    scope: Option<SearchScope>,
+    branch: Option<BranchFilter>,
+    subagents: SubagentFilter,
+    offset: usize,
+    page_token: Option<String>,
+    private: bool,
 ) -> Result<()> {
     let index = SessionIndex::open_default()?;
     ensure_index_fresh(&index)?;
+    let resume = Config::load()?.resume;
 
     // Parse time filters
     let since_dt = since.as_ref().map(|s| parse_time(s)).transpose()?;
     let until_dt = until.as_ref().map(|s| parse_time(s)).transpose()?;
 
-    let results = index.recent(limit * 2)?; // Get more to filter
+    let start = page_token.as_deref().map(parse_page_token).transpose()?;
+    let (results, next) = collect_page(
+        |after, limit| index.recent_page(after, limit),
+        list_filter(
+            &index,
+            source,
+            since_dt,
+            until_dt,
+            by_start,
+            scope.as_ref(),
+            branch.as_ref(),
+            subagents,
+            private,
+        ),
+        start,
+        offset,
+        limit,
+    )?;
 
     let output = ListOutput {
         sessions: results
             .iter()
-            // Filter by source
-            .filter(|r| source.is_none_or(|s| r.session.source == s))
-            // Filter by time
-            .filter(|r| since_dt.is_none_or(|t| r.session.timestamp >= t))
-            .filter(|r| until_dt.is_none_or(|t| r.session.timestamp <= t))
-            // Filter by working directory
-            .filter(|r| cwd.as_ref().is_none_or(|c| r.session.cwd == *c))
-            .take(limit)
-            .map(|r| r.session.to_summary())
-            .collect(),
+            .map(|r| r.session.to_summary(&resume))
+            .collect::<Result<_>>()?,
+        next_page_token: next.map(|key| key.to_token()),
     };
 
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
+/// Filter for the options `list` and `export --all` share
+#[allow(clippy::too_many_arguments)]
+fn list_filter<'a>(
+    index: &'a SessionIndex,
+    source: Option<SessionSource>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    by_start: bool,
+    scope: Option<&'a SearchScope>,
+    branch: Option<&'a BranchFilter>,
+    subagents: SubagentFilter,
+    private: bool,
+) -> impl Fn(&SearchResult) -> bool + 'a {
+    move |r| {
+        source.is_none_or(|s| r.session.source == s)
+            && since.is_none_or(|t| filter_time(r, by_start) >= t)
+            && until.is_none_or(|t| filter_time(r, by_start) <= t)
+            && scope.is_none_or(|s| s.matches(&r.session.cwd))
+            && branch.is_none_or(|b| b.matches(&r.session))
+            && subagents.matches(&r.session)
+            && !(private && index.ignore_rules().is_private(&r.session.cwd))
+    }
+}
+
 /// Run the read subcommand
-pub fn run_read(session_id: &str) -> Result<()> {
+pub fn run_read(
+    session_id: &str,
+    thread: bool,
+    include_reasoning: bool,
+    include_tools: bool,
+    commits: bool,
+) -> Result<()> {
     let index = SessionIndex::open_default()?;
     ensure_index_fresh(&index)?;
+    let redactor = load_redactor()?;
+    let resume = Config::load()?.resume;
+
+    if thread {
+        return read_thread(&index, &redactor, &resume, session_id, include_reasoning, include_tools);
+    }
 
     // Find the session by ID
     let file_path = index
@@ -265,8 +420,379 @@ pub fn run_read(session_id: &str) -> Result<()> {
         .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
 
     // Parse full session
-    let session = parser::parse_session_file(&file_path)?;
-    let output = session.to_read_output();
+    let mut session = parser::parse_session_file(&file_path)?;
+    if !include_reasoning {
+        session.strip_reasoning();
+    }
+    if !include_tools {
+        session.strip_tools();
+    }
+    redactor.redact_session(&mut session);
+    let mut output = session.to_read_output(&resume)?;
+    if commits {
+        // Look for commits where the folder is now, if it moved
+        remap::remap_session_cwd(&mut session, &Remaps::load()?);
+        output.commits = Some(git::session_commits(&session)?);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Run the export subcommand: write a session transcript to a file or stdout
+pub fn run_export(
+    session_id: &str,
+    format: ExportFormat,
+    output: Option<&Path>,
+    include_reasoning: bool,
+) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let file_path = index
+        .get_by_id(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+
+    let mut session = parser::parse_session_file(&file_path)?;
+    if !include_reasoning {
+        session.strip_reasoning();
+    }
+    load_redactor()?.redact_session(&mut session);
+    let transcript = export::export_session(&session, format, &Config::load()?.resume)?;
+
+    match output {
+        Some(path) => std::fs::write(path, transcript)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", transcript),
+    }
+    Ok(())
+}
+
+/// Run `export --all`: write every session matching the filters into a bundle
+/// (a directory, or a `.tar` file) with a manifest
+#[allow(clippy::too_many_arguments)]
+pub fn run_export_all(
+    format: ExportFormat,
+    output: &Path,
+    include_reasoning: bool,
+    source: Option<SessionSource>,
+    since: Option<String>,
+    until: Option<String>,
+    by_start: bool,
+    scope: Option<SearchScope>,
+    branch: Option<BranchFilter>,
+    subagents: SubagentFilter,
+    private: bool,
+) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+    let redactor = load_redactor()?;
+    let resume = Config::load()?.resume;
+
+    let since_dt = since.as_ref().map(|s| parse_time(s)).transpose()?;
+    let until_dt = until.as_ref().map(|s| parse_time(s)).transpose()?;
+    let keep = list_filter(
+        &index,
+        source,
+        since_dt,
+        until_dt,
+        by_start,
+        scope.as_ref(),
+        branch.as_ref(),
+        subagents,
+        private,
+    );
+
+    let mut bundle = BundleWriter::create(output)?;
+    let mut manifest = Manifest::new(format.extension());
+    let mut after = None;
+    loop {
+        let page = index.recent_page(after.as_ref(), EXPORT_BATCH)?;
+        for result in page.results.iter().filter(|r| keep(r)) {
+            let mut session = match parser::parse_session_file(&result.session.file_path) {
+                Ok(session) => session,
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", result.session.id, e);
+                    continue;
+                }
+            };
+            if !include_reasoning {
+                session.strip_reasoning();
+            }
+            redactor.redact_session(&mut session);
+
+            let entry = ManifestEntry::new(&session, format.extension());
+            bundle.add(&entry.path, export::export_session(&session, format, &resume)?.as_bytes())?;
+            manifest.sessions.push(entry);
+        }
+        match page.next {
+            Some(next) => after = Some(next),
+            None => break,
+        }
+    }
+    bundle.finish(&manifest)?;
+
+    let output = serde_json::json!({
+        "output": output,
+        "format": manifest.format,
+        "sessions": manifest.sessions.len(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Run the import subcommand: add a JSON export bundle as read-only archived sessions
+pub fn run_import(bundle: &Path) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let summary = archive::import_bundle(bundle, |id| Ok(index.get_by_id(id)?.is_some()))?;
+    // Index the imported sessions now, so they're searchable right away
+    ensure_index_fresh(&index)?;
+
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(())
+}
+
+/// Run the handoff subcommand: rewrite a session as a new session of another agent
+pub fn run_handoff(session_id: &str, target: SessionSource) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let file_path = index
+        .get_by_id(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+    let mut session = parser::parse_session_file(&file_path)?;
+    load_redactor()?.redact_session(&mut session);
+
+    let new_path = handoff::handoff(&session, target)?;
+    let new_session = parser::parse_session_file(&new_path)?;
+    ensure_index_fresh(&index)?;
+
+    let output = HandoffOutput {
+        session_id: new_session.id.clone(),
+        source: new_session.source,
+        file_path: new_path,
+        cwd: new_session.cwd.clone(),
+        from_session_id: session.id,
+        resume_command: new_session.resume_command_line(&Config::load()?.resume)?,
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Find the session to resume from a full session ID or a unique prefix of one
+pub fn resolve_session(id_or_prefix: &str) -> Result<Session> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let mut matches = index.sessions_by_id_prefix(id_or_prefix)?;
+    match matches.len() {
+        0 => anyhow::bail!("Session not found: {}", id_or_prefix),
+        1 => Ok(matches.remove(0)),
+        n => {
+            let mut message = format!(
+                "Session ID prefix '{}' is ambiguous ({} sessions):",
+                id_or_prefix, n
+            );
+            for session in matches.iter().take(MAX_AMBIGUOUS_CANDIDATES) {
+                message.push_str(&format!(
+                    "\n  {}  {}  {}  {}",
+                    session.id,
+                    session.source.as_str(),
+                    session.cwd,
+                    session.title
+                ));
+            }
+            if n > MAX_AMBIGUOUS_CANDIDATES {
+                message.push_str(&format!("\n  ...and {} more", n - MAX_AMBIGUOUS_CANDIDATES));
+            }
+            anyhow::bail!(message)
+        }
+    }
+}
+
+/// Find the most recent resumable session in the current directory
+pub fn last_session(source: Option<SessionSource>, private: bool) -> Result<Session> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let cwd = std::env::current_dir()?.to_string_lossy().to_string();
+    // Sessions from before the folder moved count too
+    let remaps = Remaps::load()?;
+    let keep = |r: &SearchResult| {
+        (r.session.cwd == cwd || remaps.resolve(&r.session.cwd).as_ref() == Some(&cwd))
+            && source.is_none_or(|s| r.session.source == s)
+            && !r.session.is_subagent
+            && !r.session.is_archived()
+            && !(private && index.ignore_rules().is_private(&r.session.cwd))
+    };
+    let (mut results, _) =
+        collect_page(|after, limit| index.recent_page(after, limit), keep, None, 0, 1)?;
+    let Some(result) = results.pop() else {
+        anyhow::bail!("No sessions found in {}", cwd);
+    };
+    Ok(result.session)
+}
+
+/// Get the working directories of all indexed sessions (the known project folders)
+pub fn known_cwds() -> Result<Vec<String>> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+    index.known_cwds()
+}
+
+/// Read every session in a thread as one conversation
+fn read_thread(
+    index: &SessionIndex,
+    redactor: &Redactor,
+    resume: &ResumeConfig,
+    session_id: &str,
+    include_reasoning: bool,
+    include_tools: bool,
+) -> Result<()> {
+    let thread = index.thread(session_id)?;
+    if thread.is_empty() {
+        anyhow::bail!("Session not found: {}", session_id);
+    }
+
+    let mut sessions = Vec::new();
+    let mut messages: Vec<ThreadMessage> = Vec::new();
+    // Continuations start with a copy of the conversation so far; keep the original
+    let mut seen = std::collections::HashSet::new();
+
+    for summary in &thread {
+        let mut session = parser::parse_session_file(&summary.file_path)?;
+        if !include_reasoning {
+            session.strip_reasoning();
+        }
+        if !include_tools {
+            session.strip_tools();
+        }
+        redactor.redact_session(&mut session);
+        for message in &session.messages {
+            let key = (message.role, message.content.clone(), message.timestamp);
+            if seen.insert(key) {
+                messages.push(ThreadMessage {
+                    session_id: session.id.clone(),
+                    message: message.clone(),
+                });
+            }
+        }
+        sessions.push(session.to_summary(resume)?);
+    }
+
+    let output = ThreadOutput {
+        session_id: session_id.to_string(),
+        sessions,
+        messages,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Redactor for session text printed to agents (see `RedactConfig`)
+fn load_redactor() -> Result<Redactor> {
+    Redactor::from_config(&Config::load()?.redact)
+}
+
+/// Run the blame subcommand: find the sessions a commit was made in, in any worktree
+/// or clone of the repository, most likely first
+pub fn run_blame(rev: &str, dir: &Path, private: bool) -> Result<()> {
+    let commit = git::show_commit(dir, rev)?;
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+    let resume = Config::load()?.resume;
+
+    let scope = SearchScope::around(ScopeKind::Worktrees, &dir.to_string_lossy(), || {
+        index.known_cwds().unwrap_or_default()
+    })
+    .ok_or_else(|| anyhow::anyhow!("{} isn't in a git repository", dir.display()))?;
+    let remaps = Remaps::load()?;
+    let grace = Duration::minutes(git::COMMIT_GRACE_MINUTES);
+
+    // Top-level sessions that were active in the repository when the commit was made
+    let keep = |r: &SearchResult| {
+        let cwd = remaps.resolve(&r.session.cwd).unwrap_or_else(|| r.session.cwd.clone());
+        scope.matches(&cwd)
+            && !r.session.is_subagent
+            && r.session.start_timestamp <= commit.timestamp
+            && commit.timestamp <= r.session.timestamp + grace
+            && !(private && index.ignore_rules().is_private(&r.session.cwd))
+    };
+    let (candidates, _) = collect_page(
+        |after, limit| index.recent_page(after, limit),
+        keep,
+        None,
+        0,
+        MAX_BLAME_CANDIDATES,
+    )?;
+
+    // Sessions with the commit among their own commits come first, then those whose
+    // last message was closest before it
+    let mut ranked: Vec<(bool, Duration, Session)> = candidates
+        .into_iter()
+        .map(|r| {
+            let mut located = r.session.clone();
+            remap::remap_session_cwd(&mut located, &remaps);
+            let made_here = git::session_commits(&located)
+                .map(|commits| commits.iter().any(|c| c.hash == commit.hash))
+                .unwrap_or(false);
+            let since_last_message = (commit.timestamp - r.session.timestamp).max(Duration::zero());
+            (!made_here, since_last_message, r.session)
+        })
+        .collect();
+    ranked.sort_by_key(|(not_made_here, since_last_message, _)| (*not_made_here, *since_last_message));
+
+    let output = BlameOutput {
+        commit,
+        sessions: ranked
+            .into_iter()
+            .map(|(_, _, s)| s.to_summary(&resume))
+            .collect::<Result<_>>()?,
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Run the related subcommand
+pub fn run_related(session_id: &str, limit: usize, private: bool) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    // Make sure the session exists so a typo isn't reported as "no related sessions"
+    index
+        .get_by_id(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+
+    let results = index.related(session_id, limit, |s| {
+        !(private && index.ignore_rules().is_private(&s.cwd))
+    })?;
+    let resume = Config::load()?.resume;
+
+    let output = RelatedOutput {
+        session_id: session_id.to_string(),
+        results: results
+            .into_iter()
+            .map(|r| {
+                let resume_command = r.session.resume_command_line(&resume)?;
+
+                Ok(RelatedResultOutput {
+                    session_id: r.session.id,
+                    title: r.session.title,
+                    source: r.session.source,
+                    cwd: r.session.cwd,
+                    timestamp: r.session.timestamp,
+                    start_timestamp: r.session.start_timestamp,
+                    active_duration_secs: r.session.active_duration.num_seconds(),
+                    score: r.score,
+                    snippet: r.snippet,
+                    resume_command,
+                })
+            })
+            .collect::<Result<_>>()?,
+    };
 
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
@@ -0,0 +1,355 @@
+//! User configuration loaded from `config.toml`
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Get the default path of the config file
+pub fn default_config_path() -> PathBuf {
+    crate::paths::config_dir().join("config.toml")
+}
+
+/// Top-level configuration (every section is optional)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub ranking: RankingConfig,
+    pub index: IndexConfig,
+    pub redact: RedactConfig,
+    pub ignore: IgnoreConfig,
+    pub encryption: EncryptionConfig,
+    pub resume: ResumeConfig,
+    pub git: GitConfig,
+}
+
+impl Config {
+    /// Load config from the default path, or use defaults if it doesn't exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&default_config_path())
+    }
+
+    /// Load config from a file, or use defaults if it doesn't exist
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).context("Failed to read config file")?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        config
+            .resume
+            .validate()
+            .with_context(|| format!("Invalid resume command in {}", path.display()))?;
+        Ok(config)
+    }
+}
+
+/// Weights used to rank search results
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    /// Half-life of the session recency boost, in days
+    pub recency_half_life_days: f64,
+    /// Maximum recency boost: a brand-new session scores up to (1 + weight)x
+    pub recency_weight: f64,
+    /// Boost for exact phrase matches in multi-word queries
+    pub phrase_boost: f32,
+    /// Bonus per message position, favoring matches later in a session
+    pub message_position_weight: f32,
+    /// Weight of matches in indexed reasoning, relative to message content
+    pub reasoning_weight: f32,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            recency_half_life_days: 7.0,
+            recency_weight: 1.0,
+            phrase_boost: 10.0,
+            message_position_weight: 0.01,
+            reasoning_weight: 0.3,
+        }
+    }
+}
+
+/// What gets indexed (changes apply to sessions indexed afterwards; use `--reindex`)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IndexConfig {
+    /// Index thinking/reasoning text so it's searchable (off by default)
+    pub include_reasoning: bool,
+}
+
+/// Git integration
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Find the commits made during each session (in its folder's repository, with
+    /// git) and show them in the preview and `recall read` (off by default)
+    pub commits: bool,
+}
+
+/// Secret redaction applied before indexing and in `read`/`search` output
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RedactConfig {
+    /// Redact secrets at all (on by default)
+    pub enabled: bool,
+    /// Extra patterns, on top of the built-in detectors
+    pub patterns: Vec<RedactPattern>,
+}
+
+impl Default for RedactConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// A user-defined secret pattern, shown as `[REDACTED:kind]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RedactPattern {
+    pub kind: String,
+    pub regex: String,
+}
+
+/// Sessions kept out of the index (more rules can go in `~/.recallignore`)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IgnoreConfig {
+    /// Working directory globs; a plain path also covers its subdirectories
+    pub cwd: Vec<String>,
+    /// Sources to skip entirely (claude, codex, factory, opencode)
+    pub sources: Vec<String>,
+    /// Session IDs
+    pub sessions: Vec<String>,
+    /// Regexes: sessions with a matching message are skipped
+    pub content: Vec<String>,
+    /// Working directory globs hidden with `--private` (still indexed)
+    pub private: Vec<String>,
+}
+
+/// Encryption of the index cache at rest
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Encrypt the index and its state (unlocked with a passphrase, or a keyfile)
+    pub enabled: bool,
+    /// Keyfile to unlock the index with, instead of a passphrase
+    pub keyfile: Option<PathBuf>,
+}
+
+/// Templates for the commands that resume sessions, by source name (claude, codex,
+/// factory, opencode). See `Session::resume_command` for the placeholders.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ResumeConfig {
+    #[serde(flatten)]
+    pub commands: HashMap<String, String>,
+    /// Templates for sessions in particular projects (the first match wins)
+    pub projects: Vec<ProjectResumeConfig>,
+    /// Target preselected when Ctrl+O opens sessions next to the TUI (tmux-window,
+    /// tmux-split, zellij, screen); by default, whichever multiplexer recall runs in
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ProjectResumeConfig {
+    /// Working directory glob, as in ignore rules (a plain path covers subdirectories)
+    pub cwd: String,
+    #[serde(flatten)]
+    pub commands: HashMap<String, String>,
+}
+
+impl ResumeConfig {
+    /// The template for a source in a project's `[[resume.projects]]` entry, if any
+    pub fn project_template(&self, source: &str, cwd: &str) -> Option<&str> {
+        self.projects
+            .iter()
+            .filter(|p| crate::ignore::cwd_matches(&crate::ignore::expand_home(&p.cwd), cwd))
+            .find_map(|p| p.commands.get(source))
+            .map(|s| s.as_str())
+    }
+
+    /// The template for a source outside of project overrides, if any
+    pub fn template(&self, source: &str) -> Option<&str> {
+        self.commands.get(source).map(|s| s.as_str())
+    }
+
+    /// Where to open sessions resumed alongside the TUI
+    pub fn target(&self) -> ResumeTarget {
+        self.target
+            .as_deref()
+            .and_then(ResumeTarget::parse)
+            .unwrap_or_else(ResumeTarget::detect)
+    }
+
+    /// Check that every template splits into a command, and the target is known
+    fn validate(&self) -> Result<()> {
+        if let Some(target) = &self.target {
+            if ResumeTarget::parse(target).is_none() {
+                anyhow::bail!(
+                    "Unknown resume target '{}'. Valid: tmux-window, tmux-split, zellij, screen",
+                    target
+                );
+            }
+        }
+        let templates = self
+            .commands
+            .values()
+            .chain(self.projects.iter().flat_map(|p| p.commands.values()));
+        for template in templates {
+            if crate::shell::split(template)?.is_empty() {
+                anyhow::bail!("Empty resume command");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where to open a resumed session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeTarget {
+    /// A new window in the current tmux session
+    TmuxWindow,
+    /// A new pane split off the current tmux pane
+    TmuxSplit,
+    /// A new pane in the current zellij session
+    Zellij,
+    /// A new detached screen session
+    Screen,
+}
+
+impl ResumeTarget {
+    pub const ALL: [ResumeTarget; 4] = [
+        ResumeTarget::TmuxWindow,
+        ResumeTarget::TmuxSplit,
+        ResumeTarget::Zellij,
+        ResumeTarget::Screen,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResumeTarget::TmuxWindow => "tmux-window",
+            ResumeTarget::TmuxSplit => "tmux-split",
+            ResumeTarget::Zellij => "zellij",
+            ResumeTarget::Screen => "screen",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == s)
+    }
+
+    /// Human-readable description (for status messages)
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ResumeTarget::TmuxWindow => "a new tmux window",
+            ResumeTarget::TmuxSplit => "a new tmux pane",
+            ResumeTarget::Zellij => "a new zellij pane",
+            ResumeTarget::Screen => "a detached screen session",
+        }
+    }
+
+    /// The target that fits the terminal multiplexer recall is running in: a tmux
+    /// window inside tmux, a zellij pane inside zellij, or else a screen session
+    pub fn detect() -> Self {
+        if std::env::var_os("TMUX").is_some() {
+            ResumeTarget::TmuxWindow
+        } else if std::env::var_os("ZELLIJ").is_some() {
+            ResumeTarget::Zellij
+        } else {
+            ResumeTarget::Screen
+        }
+    }
+
+    /// Whether the target can be opened from here (tmux and zellij targets need
+    /// recall to run inside them)
+    pub fn is_available(&self) -> bool {
+        match self {
+            ResumeTarget::TmuxWindow | ResumeTarget::TmuxSplit => std::env::var_os("TMUX").is_some(),
+            ResumeTarget::Zellij => std::env::var_os("ZELLIJ").is_some(),
+            ResumeTarget::Screen => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_config_uses_defaults() {
+        let config = Config::load_from(Path::new("/nonexistent/recall/config.toml")).unwrap();
+        assert_eq!(config.ranking, RankingConfig::default());
+        assert!(!config.index.include_reasoning);
+    }
+
+    #[test]
+    fn test_index_section() {
+        let config: Config = toml::from_str("[index]\ninclude_reasoning = true\n").unwrap();
+        assert!(config.index.include_reasoning);
+        assert_eq!(config.ranking, RankingConfig::default());
+    }
+
+    #[test]
+    fn test_redact_section() {
+        let config: Config = toml::from_str(
+            "[[redact.patterns]]\nkind = \"internal_host\"\nregex = '\\.corp\\.example\\.com'\n",
+        )
+        .unwrap();
+        assert!(config.redact.enabled);
+        assert_eq!(config.redact.patterns[0].kind, "internal_host");
+        assert_eq!(config.redact.patterns[0].regex, r"\.corp\.example\.com");
+    }
+
+    #[test]
+    fn test_resume_target_parse_round_trips() {
+        for target in ResumeTarget::ALL {
+            assert_eq!(ResumeTarget::parse(target.as_str()), Some(target));
+        }
+        assert_eq!(ResumeTarget::parse("tmux"), None);
+    }
+
+    #[test]
+    fn test_resume_section() {
+        let config: Config = toml::from_str(
+            "[resume]\nclaude = \"claude --resume {id}\"\ntarget = \"tmux-split\"\n\n\
+             [[resume.projects]]\ncwd = \"/work/legacy\"\ncodex = \"codex --profile old resume {id}\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.resume.template("claude"), Some("claude --resume {id}"));
+        assert_eq!(config.resume.template("codex"), None);
+        assert_eq!(config.resume.template("target"), None);
+        assert_eq!(config.resume.target(), ResumeTarget::TmuxSplit);
+        assert_eq!(
+            config.resume.project_template("codex", "/work/legacy/api"),
+            Some("codex --profile old resume {id}")
+        );
+        assert_eq!(config.resume.project_template("codex", "/work/new"), None);
+        assert_eq!(config.resume.project_template("claude", "/work/legacy"), None);
+
+        let bad: Config = toml::from_str("[resume]\nclaude = \"claude '{id}\"\n").unwrap();
+        assert!(bad.resume.validate().is_err());
+        let bad: Config = toml::from_str("[resume]\ntarget = \"kitty\"\n").unwrap();
+        assert!(bad.resume.validate().is_err());
+    }
+
+    #[test]
+    fn test_partial_ranking_section() {
+        let config: Config = toml::from_str("[ranking]\nphrase_boost = 3.0\n").unwrap();
+        assert_eq!(config.ranking.phrase_boost, 3.0);
+        assert_eq!(config.ranking.recency_half_life_days, 7.0);
+    }
+
+    #[test]
+    fn test_invalid_config_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[ranking]\nphrase_boost = \"high\"\n").unwrap();
+        assert!(Config::load_from(&path).is_err());
+    }
+}
@@ -0,0 +1,404 @@
+//! Transcripts of a session for sharing: markdown, standalone HTML, plain text or JSON
+
+use crate::config::ResumeConfig;
+use crate::markdown::{self, escape_html};
+use crate::session::{ContentBlock, Message, MessageKind, Role, Session};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+/// Output format for `recall export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Text,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            "html" | "htm" => Some(ExportFormat::Html),
+            "txt" | "text" => Some(ExportFormat::Text),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// File extension of transcripts in this format (also its name in bundle manifests)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Text => "txt",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Render a parsed session as a transcript in the given format
+pub fn export_session(
+    session: &Session,
+    format: ExportFormat,
+    resume: &ResumeConfig,
+) -> Result<String> {
+    Ok(match format {
+        ExportFormat::Markdown => to_markdown(session),
+        ExportFormat::Html => to_html(session),
+        ExportFormat::Text => to_text(session),
+        ExportFormat::Json => serde_json::to_string_pretty(&session.to_read_output(resume)?)? + "\n",
+    })
+}
+
+fn title(session: &Session) -> &str {
+    if session.title.trim().is_empty() {
+        "Untitled session"
+    } else {
+        &session.title
+    }
+}
+
+/// Header fields shown above the transcript, in order
+fn header_fields(session: &Session) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("Source", session.source.display_name().to_string())];
+    if !session.cwd.is_empty() {
+        fields.push(("Directory", session.cwd.clone()));
+    }
+    if let Some(branch) = &session.git_branch {
+        fields.push(("Branch", branch.clone()));
+    }
+    fields.push(("Time", time_range(session)));
+    fields.push(("Session", session.id.clone()));
+    fields
+}
+
+/// "2025-12-04 10:00 – 11:30 UTC (1h 5m active)"
+fn time_range(session: &Session) -> String {
+    let (start, end) = (session.start_timestamp, session.timestamp);
+    let end_format = if start.date_naive() == end.date_naive() {
+        "%H:%M"
+    } else {
+        "%Y-%m-%d %H:%M"
+    };
+    let mut range = format!(
+        "{} – {} UTC",
+        start.format("%Y-%m-%d %H:%M"),
+        end.format(end_format)
+    );
+    if let Some(active) = crate::ui::format_duration(session.active_duration) {
+        range.push_str(&format!(" ({} active)", active));
+    }
+    range
+}
+
+/// Who a turn is from, e.g. "User · 10:02" (with the date once the session spans days)
+fn turn_label(session: &Session, message: &Message) -> String {
+    let who = match (message.kind, message.role) {
+        (MessageKind::CompactionSummary, _) => "Summary of earlier conversation",
+        (_, Role::User) => "User",
+        (_, Role::Assistant) => "Assistant",
+    };
+    format!("{} · {}", who, turn_time(session, message.timestamp))
+}
+
+fn turn_time(session: &Session, timestamp: DateTime<Utc>) -> String {
+    if timestamp.date_naive() == session.start_timestamp.date_naive() {
+        timestamp.format("%H:%M").to_string()
+    } else {
+        timestamp.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+/// The blocks of a message (a bare text block for messages built without any)
+fn blocks(message: &Message) -> Vec<ContentBlock> {
+    if message.blocks.is_empty() {
+        vec![ContentBlock::Text {
+            text: message.content.clone(),
+        }]
+    } else {
+        message.blocks.clone()
+    }
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// Wrap text in a code fence longer than any backtick run inside it
+fn fenced(text: &str, lang: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}", fence, lang, text.trim_end_matches('\n'), fence)
+}
+
+fn to_markdown(session: &Session) -> String {
+    let mut out = format!("# {}\n\n", title(session));
+    for (name, value) in header_fields(session) {
+        match name {
+            "Directory" | "Branch" | "Session" => {
+                out.push_str(&format!("- **{}:** `{}`\n", name, value))
+            }
+            _ => out.push_str(&format!("- **{}:** {}\n", name, value)),
+        }
+    }
+
+    for message in &session.messages {
+        out.push_str(&format!("\n---\n\n## {}\n", turn_label(session, message)));
+        for block in blocks(message) {
+            let part = match block {
+                ContentBlock::Text { text } if text.trim().is_empty() => continue,
+                ContentBlock::Text { text } => text.trim_end().to_string(),
+                ContentBlock::Reasoning { text } => format!(
+                    "<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>",
+                    text.trim_end()
+                ),
+                ContentBlock::ToolUse { name, input } => {
+                    format!("**Tool call:** `{}`\n\n{}", name, fenced(&pretty_json(&input), "json"))
+                }
+                ContentBlock::ToolResult { output, is_error } => format!(
+                    "**{}:**\n\n{}",
+                    if is_error { "Tool error" } else { "Tool result" },
+                    fenced(&output, "")
+                ),
+                ContentBlock::Image { media_type, source } => format!(
+                    "*[image: {}]*",
+                    source.or(media_type).unwrap_or_else(|| "inline".to_string())
+                ),
+                ContentBlock::Attachment { path } => format!("*[attachment: `{}`]*", path),
+            };
+            out.push('\n');
+            out.push_str(&part);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn to_text(session: &Session) -> String {
+    let title = title(session);
+    let mut out = format!("{}\n{}\n", title, "=".repeat(title.chars().count()));
+    for (name, value) in header_fields(session) {
+        out.push_str(&format!("{}: {}\n", name, value));
+    }
+
+    for message in &session.messages {
+        out.push_str(&format!("\n[{}]\n", turn_label(session, message)));
+        for block in blocks(message) {
+            let part = match block {
+                ContentBlock::Text { text } if text.trim().is_empty() => continue,
+                ContentBlock::Text { text } => text.trim_end().to_string(),
+                ContentBlock::Reasoning { text } => {
+                    format!("(thinking)\n{}", indent(text.trim_end(), "  "))
+                }
+                ContentBlock::ToolUse { name, input } => format!("(tool call: {}) {}", name, input),
+                ContentBlock::ToolResult { output, is_error } => format!(
+                    "({})\n{}",
+                    if is_error { "tool error" } else { "tool result" },
+                    indent(output.trim_end(), "    ")
+                ),
+                ContentBlock::Image { media_type, source } => format!(
+                    "(image: {})",
+                    source.or(media_type).unwrap_or_else(|| "inline".to_string())
+                ),
+                ContentBlock::Attachment { path } => format!("(attachment: {})", path),
+            };
+            out.push_str(&part);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}", prefix, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const HTML_STYLE: &str = r#":root {
+  color-scheme: light dark;
+  --bg: #ffffff; --fg: #1f2328; --muted: #656d76; --border: #d0d7de;
+  --user: #0969da; --assistant: #8250df; --code-bg: #f6f8fa;
+  --kw: #cf222e; --str: #0a3069; --com: #6e7781; --num: #0550ae;
+}
+@media (prefers-color-scheme: dark) {
+  :root {
+    --bg: #0d1117; --fg: #e6edf3; --muted: #8d96a0; --border: #30363d;
+    --user: #4493f8; --assistant: #ab7df8; --code-bg: #161b22;
+    --kw: #ff7b72; --str: #a5d6ff; --com: #8b949e; --num: #79c0ff;
+  }
+}
+body { margin: 0; background: var(--bg); color: var(--fg);
+  font: 15px/1.6 -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; }
+main, header { max-width: 860px; margin: 0 auto; padding: 0 24px; }
+header { padding-top: 32px; border-bottom: 1px solid var(--border); }
+header h1 { margin: 0 0 12px; font-size: 1.6em; }
+dl.meta { display: grid; grid-template-columns: max-content 1fr; gap: 2px 16px; margin: 0 0 24px; }
+dl.meta dt { color: var(--muted); }
+dl.meta dd { margin: 0; }
+.turn { padding: 16px 0; border-bottom: 1px solid var(--border); }
+.turn h2 { margin: 0 0 8px; font-size: 0.95em; }
+.turn h2 time { color: var(--muted); font-weight: normal; }
+.turn.user h2 { color: var(--user); }
+.turn.assistant h2, .turn.summary h2 { color: var(--assistant); }
+code { font: 0.9em ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }
+:not(pre) > code { background: var(--code-bg); padding: 1px 4px; border-radius: 4px; }
+pre { background: var(--code-bg); padding: 12px; border-radius: 6px; overflow-x: auto; }
+blockquote { margin: 0; padding-left: 12px; border-left: 3px solid var(--border); color: var(--muted); }
+details { margin: 8px 0; }
+summary { cursor: pointer; color: var(--muted); }
+.error summary { color: var(--kw); }
+.note { color: var(--muted); font-style: italic; }
+.kw { color: var(--kw); } .str { color: var(--str); } .com { color: var(--com); } .num { color: var(--num); }
+"#;
+
+fn to_html(session: &Session) -> String {
+    let title = escape_html(title(session));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{0}</title>\n<style>\n{1}</style>\n</head>\n<body>\n<header>\n<h1>{0}</h1>\n<dl class=\"meta\">\n",
+        title, HTML_STYLE
+    );
+    for (name, value) in header_fields(session) {
+        out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", name, escape_html(&value)));
+    }
+    out.push_str("</dl>\n</header>\n<main>\n");
+
+    for message in &session.messages {
+        let (class, who) = match (message.kind, message.role) {
+            (MessageKind::CompactionSummary, _) => ("summary", "Summary of earlier conversation"),
+            (_, Role::User) => ("user", "User"),
+            (_, Role::Assistant) => ("assistant", "Assistant"),
+        };
+        out.push_str(&format!(
+            "<article class=\"turn {}\">\n<h2>{} <time datetime=\"{}\">{}</time></h2>\n",
+            class,
+            who,
+            message.timestamp.to_rfc3339(),
+            turn_time(session, message.timestamp)
+        ));
+        for block in blocks(message) {
+            let part = match block {
+                ContentBlock::Text { text } if text.trim().is_empty() => continue,
+                ContentBlock::Text { text } => markdown::to_html(&text),
+                ContentBlock::Reasoning { text } => format!(
+                    "<details class=\"thinking\"><summary>Thinking</summary>\n{}</details>\n",
+                    markdown::to_html(&text)
+                ),
+                ContentBlock::ToolUse { name, input } => format!(
+                    "<details class=\"tool\"><summary>Tool call: <code>{}</code></summary>\n<pre><code>{}</code></pre></details>\n",
+                    escape_html(&name),
+                    escape_html(&pretty_json(&input))
+                ),
+                ContentBlock::ToolResult { output, is_error } => format!(
+                    "<details class=\"{}\"><summary>{}</summary>\n<pre><code>{}</code></pre></details>\n",
+                    if is_error { "result error" } else { "result" },
+                    if is_error { "Tool error" } else { "Tool result" },
+                    escape_html(output.trim_end())
+                ),
+                ContentBlock::Image { media_type, source } => format!(
+                    "<p class=\"note\">[image: {}]</p>\n",
+                    escape_html(&source.or(media_type).unwrap_or_else(|| "inline".to_string()))
+                ),
+                ContentBlock::Attachment { path } => format!(
+                    "<p class=\"note\">[attachment: <code>{}</code>]</p>\n",
+                    escape_html(&path)
+                ),
+            };
+            out.push_str(&part);
+        }
+        out.push_str("</article>\n");
+    }
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn session() -> Session {
+        let at = |m: u32| Utc.with_ymd_and_hms(2025, 12, 4, 10, m, 0).unwrap();
+        Session::test("abc-123")
+            .with_cwd("/work/app")
+            .with_branch("main")
+            .with_title("Fix the <parser>")
+            .with_times(at(0), at(30), chrono::Duration::minutes(25))
+            .with_messages(vec![
+                Message::text(Role::User, "why does `parse` fail?", at(0)),
+                Message::new(
+                    Role::Assistant,
+                    vec![
+                        ContentBlock::Text {
+                            text: "Let me check.".to_string(),
+                        },
+                        ContentBlock::ToolUse {
+                            name: "Bash".to_string(),
+                            input: serde_json::json!({"command": "cargo test"}),
+                        },
+                        ContentBlock::ToolResult {
+                            output: "```\nfailed\n```".to_string(),
+                            is_error: true,
+                        },
+                    ],
+                    at(2),
+                ),
+            ])
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(ExportFormat::parse("md"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::parse("txt"), Some(ExportFormat::Text));
+        assert_eq!(ExportFormat::parse("pdf"), None);
+        assert_eq!(ExportFormat::parse(ExportFormat::Html.extension()), Some(ExportFormat::Html));
+    }
+
+    #[test]
+    fn test_markdown_transcript() {
+        let md = export_session(&session(), ExportFormat::Markdown, &ResumeConfig::default()).unwrap();
+        assert!(md.starts_with("# Fix the <parser>\n\n- **Source:** Claude\n"));
+        assert!(md.contains("- **Branch:** `main`\n"));
+        assert!(md.contains("- **Time:** 2025-12-04 10:00 – 10:30 UTC (25m active)\n"));
+        assert!(md.contains("## User · 10:00\n\nwhy does `parse` fail?\n"));
+        assert!(md.contains("**Tool call:** `Bash`\n\n```json\n{\n  \"command\": \"cargo test\"\n}\n```"));
+        // Fences in tool output don't close the block around it
+        assert!(md.contains("**Tool error:**\n\n````\n```\nfailed\n```\n````"));
+    }
+
+    #[test]
+    fn test_text_transcript() {
+        let txt = export_session(&session(), ExportFormat::Text, &ResumeConfig::default()).unwrap();
+        assert!(txt.starts_with("Fix the <parser>\n================\nSource: Claude\n"));
+        assert!(txt.contains("[Assistant · 10:02]\nLet me check.\n(tool call: Bash) {\"command\":\"cargo test\"}\n(tool error)\n    ```"));
+    }
+
+    #[test]
+    fn test_html_is_standalone_and_escaped() {
+        let html = export_session(&session(), ExportFormat::Html, &ResumeConfig::default()).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Fix the &lt;parser&gt;</title>"));
+        assert!(html.contains("@media (prefers-color-scheme: dark)"));
+        assert!(html.contains("<p>why does <code>parse</code> fail?</p>"));
+        assert!(html.contains("<details class=\"result error\"><summary>Tool error</summary>"));
+        assert!(!html.contains("<script") && !html.contains("<link"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_json_matches_read_output() {
+        let json = export_session(&session(), ExportFormat::Json, &ResumeConfig::default()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["session_id"], "abc-123");
+        assert_eq!(value["messages"][1]["blocks"][1]["name"], "Bash");
+    }
+}
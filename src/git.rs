@@ -0,0 +1,261 @@
+//! Git repository layout, read from the filesystem, and the commits made during a
+//! session (which run git, when it's installed)
+
+use crate::session::Session;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Commits made up to this long after a session's last message count as made in it
+/// (committing is often the last step, after the agent's final reply)
+pub const COMMIT_GRACE_MINUTES: i64 = 10;
+
+/// `git log` format of commits: fields separated by the unit separator
+const LOG_FORMAT: &str = "--format=%H%x1f%an%x1f%cI%x1f%s";
+
+/// A commit, as correlated with the session it was made in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    /// Commit (not author) time, which is when it was made in the session
+    pub timestamp: DateTime<Utc>,
+    pub subject: String,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+/// The root folder of the repository or worktree containing a directory
+pub fn repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|a| a.join(".git").exists())
+        .map(|a| a.to_path_buf())
+}
+
+/// The git directory shared by all worktrees of the repository containing a
+/// directory (a worktree's `.git` file points to its own git dir, whose `commondir`
+/// is the main one)
+pub fn common_dir(dir: &Path) -> Option<PathBuf> {
+    let dot_git = repo_root(dir)?.join(".git");
+    let common_dir = if dot_git.is_dir() {
+        dot_git
+    } else {
+        let content = std::fs::read_to_string(&dot_git).ok()?;
+        let git_dir = dot_git.parent()?.join(content.trim().strip_prefix("gitdir:")?.trim());
+        match std::fs::read_to_string(git_dir.join("commondir")) {
+            Ok(common) => git_dir.join(common.trim()),
+            Err(_) => git_dir,
+        }
+    };
+    Some(std::fs::canonicalize(&common_dir).unwrap_or(common_dir))
+}
+
+/// URL of the remote of the repository containing a directory (origin, or else
+/// the first remote)
+pub fn remote_url(dir: &Path) -> Option<String> {
+    let config = std::fs::read_to_string(common_dir(dir)?.join("config")).ok()?;
+
+    let mut urls = Vec::new();
+    let mut remote: Option<String> = None;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            remote = line
+                .strip_prefix("[remote \"")
+                .and_then(|r| r.strip_suffix("\"]"))
+                .map(|r| r.to_string());
+        } else if let (Some(name), Some((key, value))) = (&remote, line.split_once('=')) {
+            if key.trim() == "url" {
+                urls.push((name.clone(), value.trim().to_string()));
+            }
+        }
+    }
+    urls.iter()
+        .find(|(name, _)| name == "origin")
+        .or_else(|| urls.first())
+        .map(|(_, url)| url.clone())
+}
+
+/// Name of the repository a remote URL points to, e.g. `api` for
+/// `git@github.com:acme/api.git`
+pub fn repo_name(url: &str) -> Option<String> {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()?
+        .trim_end_matches(".git");
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// A remote URL without its scheme, user and `.git` suffix, so the SSH and HTTPS
+/// URLs of a repository compare equal (`github.com/acme/api`)
+pub fn normalize_remote(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/').trim_end_matches(".git");
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split_once('@').map_or(url, |(_, rest)| rest);
+    // scp-like syntax (host:path) uses a colon where URLs have a slash
+    url.replacen(':', "/", 1).to_lowercase()
+}
+
+/// The commits made during a session, in its folder's repository on the branch it
+/// was on (or else the current branch), oldest first. Empty if the folder isn't in a
+/// git repository (anymore).
+pub fn session_commits(session: &Session) -> Result<Vec<Commit>> {
+    let dir = Path::new(&session.cwd);
+    if session.cwd.is_empty() || !dir.is_dir() || repo_root(dir).is_none() {
+        return Ok(Vec::new());
+    }
+    let until = session.timestamp + Duration::minutes(COMMIT_GRACE_MINUTES);
+    let branch = session
+        .git_branch
+        .as_deref()
+        .filter(|b| git(dir, &["rev-parse", "--verify", "--quiet", &format!("refs/heads/{}", b)]).is_ok());
+    commits_between(dir, branch.unwrap_or("HEAD"), session.start_timestamp, until)
+}
+
+/// Commits reachable from `rev` that were made between two times, oldest first
+pub fn commits_between(
+    dir: &Path,
+    rev: &str,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Vec<Commit>> {
+    let output = git(
+        dir,
+        &[
+            "log",
+            "--reverse",
+            LOG_FORMAT,
+            &format!("--since={}", since.to_rfc3339()),
+            &format!("--until={}", until.to_rfc3339()),
+            rev,
+            "--",
+        ],
+    )?;
+    // git stops at the first commit older than --since, but commit dates aren't always
+    // in order: keep only the commits in range
+    Ok(parse_log(&output)
+        .into_iter()
+        .filter(|c| c.timestamp >= since && c.timestamp <= until)
+        .collect())
+}
+
+/// Look up a commit by hash (or any revision) in the repository containing `dir`
+pub fn show_commit(dir: &Path, rev: &str) -> Result<Commit> {
+    // The revision comes from the user: never let git take it for an option
+    let rev_arg = format!("{}^{{commit}}", rev);
+    let output = git(
+        dir,
+        &["show", "--no-patch", LOG_FORMAT, "--end-of-options", &rev_arg, "--"],
+    )
+    .with_context(|| format!("Commit not found: {}", rev))?;
+    parse_log(&output)
+        .pop()
+        .with_context(|| format!("Commit not found: {}", rev))
+}
+
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            let hash = fields.next()?.to_string();
+            let author = fields.next()?.to_string();
+            let timestamp = DateTime::parse_from_rfc3339(fields.next()?).ok()?.with_timezone(&Utc);
+            let subject = fields.next().unwrap_or("").to_string();
+            Some(Commit {
+                hash,
+                author,
+                timestamp,
+                subject,
+            })
+        })
+        .collect()
+}
+
+/// Run git in a directory, returning its output
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_remote() {
+        let expected = "github.com/acme/api";
+        assert_eq!(normalize_remote("git@github.com:acme/api.git"), expected);
+        assert_eq!(normalize_remote("https://github.com/acme/api"), expected);
+        assert_eq!(normalize_remote("ssh://git@github.com/Acme/api.git/"), expected);
+        assert_eq!(repo_name("git@github.com:acme/api.git").as_deref(), Some("api"));
+    }
+
+    /// Commit an empty change at a given time
+    fn commit_at(dir: &Path, subject: &str, time: &str) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=Dev", "-c", "user.email=dev@example.com"])
+            .args(["commit", "--allow-empty", "--quiet", "-m", subject])
+            .env("GIT_AUTHOR_DATE", time)
+            .env("GIT_COMMITTER_DATE", time)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_session_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "--quiet", "--initial-branch=main"]).unwrap();
+        commit_at(repo, "Before the session", "2025-03-01T09:00:00Z");
+        commit_at(repo, "Fix the flaky test", "2025-03-01T10:30:00Z");
+        commit_at(repo, "Update changelog", "2025-03-01T11:05:00Z");
+        commit_at(repo, "Much later", "2025-03-01T15:00:00Z");
+
+        let session = Session::test("s1")
+            .with_cwd(repo.to_string_lossy())
+            .with_branch("main")
+            .with_title("Fix the flaky test")
+            // The changelog commit comes within the grace period
+            .with_times(
+                "2025-03-01T10:00:00Z".parse().unwrap(),
+                "2025-03-01T11:00:00Z".parse().unwrap(),
+                Duration::zero(),
+            );
+        let commits = session_commits(&session).unwrap();
+        let subjects: Vec<&str> = commits.iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(subjects, ["Fix the flaky test", "Update changelog"]);
+        assert_eq!(commits[0].author, "Dev");
+
+        let shown = show_commit(repo, commits[0].short_hash()).unwrap();
+        assert_eq!(shown, commits[0]);
+        assert!(show_commit(repo, "0000000").is_err());
+        // A revision that looks like an option isn't taken for one
+        let option = format!("--output={}", repo.join("written").display());
+        assert!(show_commit(repo, &option).is_err());
+        let written = std::fs::read_dir(repo).unwrap().flatten();
+        assert!(!written.into_iter().any(|e| e.file_name().to_string_lossy().starts_with("written")));
+
+        // Outside a repository there are no commits
+        let elsewhere = tempfile::tempdir().unwrap();
+        let session = session.with_cwd(elsewhere.path().to_string_lossy());
+        assert!(session_commits(&session).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,423 @@
+//! Handing a conversation off to another agent: rewriting it as a new session in
+//! the target tool's native format, so the tool can resume it with the context
+
+use crate::session::{ContentBlock, MessageKind, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Tool output longer than this (in characters) is cut short in the handed-off transcript
+const MAX_TOOL_OUTPUT_CHARS: usize = 4000;
+
+/// Codex CLI version whose rollout format handed-off sessions follow
+const CODEX_CLI_VERSION: &str = "0.52.0";
+
+/// Write a session as a new session of `target`, returning the new session file.
+/// Tool calls and their results become text, since tools differ between agents.
+pub fn handoff(session: &Session, target: SessionSource) -> Result<PathBuf> {
+    let home = crate::paths::home_dir().context("Couldn't find the home directory")?;
+    handoff_in(&home, session, target)
+}
+
+fn handoff_in(home: &Path, session: &Session, target: SessionSource) -> Result<PathBuf> {
+    if target == session.source && !session.is_archived() {
+        anyhow::bail!(
+            "Session {} is already a {} session",
+            session.id,
+            target.display_name()
+        );
+    }
+    let turns = turns(session);
+    match target {
+        SessionSource::ClaudeCode => write_claude(home, session, &turns),
+        SessionSource::CodexCli => write_codex(home, session, &turns),
+        SessionSource::OpenCode => write_opencode(home, session, &turns),
+        SessionSource::Factory => anyhow::bail!("Handoff to Factory isn't supported"),
+    }
+}
+
+/// A conversation turn as plain text
+#[derive(Debug, PartialEq)]
+struct Turn {
+    role: Role,
+    text: String,
+    timestamp: DateTime<Utc>,
+}
+
+/// Flatten a session into alternating user/assistant turns, starting with a user
+/// turn that says where the conversation came from. Reasoning is left out.
+fn turns(session: &Session) -> Vec<Turn> {
+    let mut turns = vec![Turn {
+        role: Role::User,
+        text: format!(
+            "[Handoff from {} session {}: {}]\nThis is the conversation so far. Continue from where it left off.",
+            session.source.display_name(),
+            session.id,
+            session.title
+        ),
+        timestamp: session.start_timestamp,
+    }];
+
+    for message in &session.messages {
+        let mut parts = Vec::new();
+        if message.kind == MessageKind::CompactionSummary {
+            parts.push("Summary of the earlier conversation:".to_string());
+        }
+        for block in &message.blocks {
+            match block {
+                ContentBlock::Text { text } if !text.trim().is_empty() => {
+                    parts.push(text.trim_end().to_string())
+                }
+                ContentBlock::ToolUse { name, input } => {
+                    parts.push(format!("[Tool call: {}] {}", name, input))
+                }
+                ContentBlock::ToolResult { output, is_error } => parts.push(format!(
+                    "[{}]\n{}",
+                    if *is_error { "Tool error" } else { "Tool result" },
+                    truncate(output.trim_end(), MAX_TOOL_OUTPUT_CHARS)
+                )),
+                ContentBlock::Image { source, .. } => parts.push(format!(
+                    "[Image: {}]",
+                    source.as_deref().unwrap_or("inline")
+                )),
+                ContentBlock::Attachment { path } => parts.push(format!("[Attachment: {}]", path)),
+                _ => {}
+            }
+        }
+        if message.blocks.is_empty() && !message.content.trim().is_empty() {
+            parts.push(message.content.clone());
+        }
+        if parts.is_empty() {
+            continue;
+        }
+
+        let text = parts.join("\n\n");
+        match turns.last_mut() {
+            Some(last) if last.role == message.role => {
+                last.text.push_str("\n\n");
+                last.text.push_str(&text);
+                last.timestamp = message.timestamp;
+            }
+            _ => turns.push(Turn {
+                role: message.role,
+                text,
+                timestamp: message.timestamp,
+            }),
+        }
+    }
+    turns
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}\n… (truncated)", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn new_uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+fn timestamp_str(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+/// Write JSON values as lines of a new file (creating its directory)
+fn write_jsonl(path: &Path, lines: &[serde_json::Value]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::File::create_new(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    for line in lines {
+        writeln!(file, "{}", line)?;
+    }
+    Ok(())
+}
+
+fn write_json(path: &Path, value: &serde_json::Value) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_vec_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Claude Code: ~/.claude/projects/<cwd with separators as dashes>/<uuid>.jsonl
+fn write_claude(home: &Path, session: &Session, turns: &[Turn]) -> Result<PathBuf> {
+    let id = new_uuid();
+    let project: String = session
+        .cwd
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let path = home
+        .join(".claude/projects")
+        .join(project)
+        .join(format!("{}.jsonl", id));
+
+    let mut lines = Vec::new();
+    let mut parent: Option<String> = None;
+    for turn in turns {
+        let uuid = new_uuid();
+        let message = match turn.role {
+            Role::User => json!({"role": "user", "content": turn.text}),
+            Role::Assistant => json!({
+                "id": format!("msg_{}", uuid.replace('-', "")),
+                "type": "message",
+                "role": "assistant",
+                "model": "<synthetic>",
+                "content": [{"type": "text", "text": turn.text}],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": {"input_tokens": 0, "output_tokens": 0},
+            }),
+        };
+        lines.push(json!({
+            "parentUuid": parent,
+            "isSidechain": false,
+            "userType": "external",
+            "cwd": session.cwd,
+            "sessionId": id,
+            "gitBranch": session.git_branch.clone().unwrap_or_default(),
+            "type": turn.role.as_str(),
+            "message": message,
+            "uuid": uuid,
+            "timestamp": timestamp_str(turn.timestamp),
+        }));
+        parent = Some(uuid);
+    }
+    lines.push(json!({"type": "summary", "summary": session.title, "leafUuid": parent}));
+
+    write_jsonl(&path, &lines)?;
+    Ok(path)
+}
+
+/// Codex CLI: ~/.codex/sessions/YYYY/MM/DD/rollout-<time>-<uuid>.jsonl
+fn write_codex(home: &Path, session: &Session, turns: &[Turn]) -> Result<PathBuf> {
+    let id = new_uuid();
+    let now = Utc::now();
+    let path = home
+        .join(".codex/sessions")
+        .join(now.format("%Y/%m/%d").to_string())
+        .join(format!("rollout-{}-{}.jsonl", now.format("%Y-%m-%dT%H-%M-%S"), id));
+
+    let mut meta = json!({
+        "id": id,
+        "timestamp": timestamp_str(session.start_timestamp),
+        "cwd": session.cwd,
+        "originator": "codex_cli_rs",
+        "cli_version": CODEX_CLI_VERSION,
+        "instructions": null,
+    });
+    if let Some(branch) = &session.git_branch {
+        meta["git"] = json!({"branch": branch});
+    }
+    let mut lines = vec![json!({
+        "timestamp": timestamp_str(session.start_timestamp),
+        "type": "session_meta",
+        "payload": meta,
+    })];
+    for turn in turns {
+        let content_type = match turn.role {
+            Role::User => "input_text",
+            Role::Assistant => "output_text",
+        };
+        lines.push(json!({
+            "timestamp": timestamp_str(turn.timestamp),
+            "type": "response_item",
+            "payload": {
+                "type": "message",
+                "role": turn.role.as_str(),
+                "content": [{"type": content_type, "text": turn.text}],
+            },
+        }));
+    }
+
+    write_jsonl(&path, &lines)?;
+    Ok(path)
+}
+
+/// OpenCode: a session file under storage/session/<project>, plus a file per message
+/// (storage/message/<session>) and per message part (storage/part/<message>)
+fn write_opencode(home: &Path, session: &Session, turns: &[Turn]) -> Result<PathBuf> {
+    let storage = home.join(".local/share/opencode/storage");
+    let project = opencode_project(&storage, &session.cwd);
+    let created = Utc::now().timestamp_millis();
+    let session_id = opencode_id("ses", created, 0);
+    let path = storage
+        .join("session")
+        .join(&project)
+        .join(format!("{}.json", session_id));
+
+    let mut parent: Option<String> = None;
+    for (i, turn) in turns.iter().enumerate() {
+        let message_id = opencode_id("msg", created, i);
+        let time = turn.timestamp.timestamp_millis();
+        let message = match turn.role {
+            Role::User => json!({
+                "id": message_id,
+                "sessionID": session_id,
+                "role": "user",
+                "time": {"created": time},
+            }),
+            Role::Assistant => json!({
+                "id": message_id,
+                "sessionID": session_id,
+                "role": "assistant",
+                "time": {"created": time, "completed": time},
+                "parentID": parent,
+                "modelID": "handoff",
+                "providerID": "recall",
+                "mode": "build",
+                "path": {"cwd": session.cwd, "root": session.cwd},
+                "cost": 0,
+                "tokens": {"input": 0, "output": 0, "reasoning": 0, "cache": {"read": 0, "write": 0}},
+            }),
+        };
+        write_json(
+            &storage.join("message").join(&session_id).join(format!("{}.json", message_id)),
+            &message,
+        )?;
+
+        let part_id = opencode_id("prt", created, i);
+        write_json(
+            &storage.join("part").join(&message_id).join(format!("{}.json", part_id)),
+            &json!({
+                "id": part_id,
+                "sessionID": session_id,
+                "messageID": message_id,
+                "type": "text",
+                "text": turn.text,
+            }),
+        )?;
+        parent = Some(message_id);
+    }
+
+    write_json(
+        &path,
+        &json!({
+            "id": session_id,
+            "version": "handoff",
+            "projectID": project,
+            "directory": session.cwd,
+            "title": session.title,
+            "time": {"created": created, "updated": created},
+        }),
+    )?;
+    Ok(path)
+}
+
+/// The OpenCode project a directory belongs to ("global" if it has none yet)
+fn opencode_project(storage: &Path, cwd: &str) -> String {
+    let Ok(entries) = std::fs::read_dir(storage.join("project")) else {
+        return "global".to_string();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let project: serde_json::Value =
+                serde_json::from_slice(&std::fs::read(entry.path()).ok()?).ok()?;
+            (project.get("worktree")?.as_str()? == cwd)
+                .then(|| project.get("id")?.as_str().map(|s| s.to_string()))
+                .flatten()
+        })
+        .next()
+        .unwrap_or_else(|| "global".to_string())
+}
+
+/// An OpenCode-style ID: a prefix, then hex digits that sort by creation
+fn opencode_id(prefix: &str, millis: i64, seq: usize) -> String {
+    let random = uuid::Uuid::new_v4().simple().to_string();
+    format!("{}_{:012x}{:04x}{}", prefix, millis, seq, &random[..10])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_session_file;
+    use crate::session::Message;
+    use chrono::TimeZone;
+
+    fn session() -> Session {
+        let at = |m: u32| Utc.with_ymd_and_hms(2025, 12, 8, 9, m, 0).unwrap();
+        Session::test("orig-1")
+            .with_cwd("/work/app")
+            .with_branch("fix-login")
+            .with_title("Fix the login redirect")
+            .with_times(at(0), at(10), chrono::Duration::minutes(10))
+            .with_messages(vec![
+                Message::text(Role::User, "the login redirect loops", at(0)),
+                Message::new(
+                    Role::Assistant,
+                    vec![
+                        ContentBlock::Reasoning {
+                            text: "check the middleware".to_string(),
+                        },
+                        ContentBlock::ToolUse {
+                            name: "Read".to_string(),
+                            input: json!({"file_path": "auth.rs"}),
+                        },
+                        ContentBlock::ToolResult {
+                            output: "x".repeat(MAX_TOOL_OUTPUT_CHARS + 10),
+                            is_error: false,
+                        },
+                        ContentBlock::Text {
+                            text: "The cookie path is wrong.".to_string(),
+                        },
+                    ],
+                    at(5),
+                ),
+            ])
+    }
+
+    #[test]
+    fn test_turns() {
+        let turns = turns(&session());
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].role, Role::User);
+        assert!(turns[0]
+            .text
+            .starts_with("[Handoff from Claude session orig-1: Fix the login redirect]"));
+        assert!(turns[0].text.ends_with("\n\nthe login redirect loops"));
+        assert!(turns[1].text.starts_with("[Tool call: Read] {\"file_path\":\"auth.rs\"}\n\n[Tool result]\n"));
+        assert!(turns[1].text.contains("… (truncated)"));
+        assert!(turns[1].text.ends_with("The cookie path is wrong."));
+        assert!(!turns[1].text.contains("middleware"));
+    }
+
+    #[test]
+    fn test_handoff_files_parse_as_the_target() {
+        let home = tempfile::tempdir().unwrap();
+        for target in [SessionSource::CodexCli, SessionSource::OpenCode] {
+            let path = handoff_in(home.path(), &session(), target).unwrap();
+            let parsed = parse_session_file(&path).unwrap();
+            assert_eq!(parsed.source, target);
+            assert_eq!(parsed.cwd, "/work/app");
+            assert_eq!(parsed.messages.len(), 2);
+            assert_eq!(parsed.messages[1].role, Role::Assistant);
+            assert!(parsed.messages[1].content.ends_with("The cookie path is wrong."));
+        }
+
+        let mut codex = session();
+        codex.source = SessionSource::CodexCli;
+        let path = handoff_in(home.path(), &codex, SessionSource::ClaudeCode).unwrap();
+        assert!(path.starts_with(home.path().join(".claude/projects/-work-app")));
+        let parsed = parse_session_file(&path).unwrap();
+        assert_eq!(parsed.id, path.file_stem().unwrap().to_str().unwrap());
+        assert_eq!(parsed.parent_id, None);
+        assert_eq!(parsed.title, "Fix the login redirect");
+        assert_eq!(parsed.git_branch.as_deref(), Some("fix-login"));
+        assert_eq!(parsed.messages[0].content.lines().next(), Some("[Handoff from Codex session orig-1: Fix the login redirect]"));
+    }
+
+    #[test]
+    fn test_rejects_same_source_and_factory() {
+        let home = tempfile::tempdir().unwrap();
+        assert!(handoff_in(home.path(), &session(), SessionSource::ClaudeCode).is_err());
+        assert!(handoff_in(home.path(), &session(), SessionSource::Factory).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! Ignore rules: sessions kept out of the index, and projects hidden with `--private`
+
+use crate::config::IgnoreConfig;
+use crate::paths;
+use crate::session::{ContentBlock, Message, Session, SessionSource};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Get the path of the user's ignore file (`~/.recallignore`)
+pub fn default_ignore_file_path() -> Option<PathBuf> {
+    paths::home_dir().map(|h| h.join(".recallignore"))
+}
+
+/// Rules from the `[ignore]` config section and `~/.recallignore`
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    /// Working directory globs (a plain path also covers its subdirectories)
+    cwd: Vec<String>,
+    sources: Vec<SessionSource>,
+    sessions: Vec<String>,
+    /// Sessions with a message matching any of these are ignored
+    content: Vec<Regex>,
+    /// Working directory globs hidden in private mode (still indexed)
+    private: Vec<String>,
+}
+
+impl IgnoreRules {
+    /// Load rules from the config section plus `~/.recallignore`, if it exists
+    pub fn load(config: &IgnoreConfig) -> Result<Self> {
+        let ignore_file = match default_ignore_file_path() {
+            Some(path) if path.exists() => std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+            _ => String::new(),
+        };
+        Self::from_config(config, &ignore_file)
+    }
+
+    /// Build rules from the config section and the contents of an ignore file.
+    ///
+    /// Ignore file lines are `kind:value` (`cwd:`, `source:`, `session:`, `content:`,
+    /// `private:`); a line without a kind is a cwd glob, and `#` starts a comment.
+    pub fn from_config(config: &IgnoreConfig, ignore_file: &str) -> Result<Self> {
+        let mut rules = Self::default();
+        for glob in &config.cwd {
+            rules.cwd.push(expand_home(glob));
+        }
+        for source in &config.sources {
+            rules.add_source(source)?;
+        }
+        rules.sessions.extend(config.sessions.iter().cloned());
+        for pattern in &config.content {
+            rules.add_content(pattern)?;
+        }
+        for glob in &config.private {
+            rules.private.push(expand_home(glob));
+        }
+
+        for (lineno, line) in ignore_file.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (kind, value) = match line.split_once(':') {
+                Some((kind, value))
+                    if matches!(kind, "cwd" | "source" | "session" | "content" | "private") =>
+                {
+                    (kind, value.trim())
+                }
+                _ => ("cwd", line),
+            };
+            match kind {
+                "cwd" => rules.cwd.push(expand_home(value)),
+                "source" => rules.add_source(value).with_context(|| line_context(lineno))?,
+                "session" => rules.sessions.push(value.to_string()),
+                "content" => rules.add_content(value).with_context(|| line_context(lineno))?,
+                _ => rules.private.push(expand_home(value)),
+            }
+        }
+        Ok(rules)
+    }
+
+    fn add_source(&mut self, source: &str) -> Result<()> {
+        let source = SessionSource::parse(source).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid ignored source '{}'. Valid: claude, codex, factory, opencode",
+                source
+            )
+        })?;
+        self.sources.push(source);
+        Ok(())
+    }
+
+    fn add_content(&mut self, pattern: &str) -> Result<()> {
+        let regex = Regex::new(pattern)
+            .with_context(|| format!("Invalid ignored content pattern {:?}", pattern))?;
+        self.content.push(regex);
+        Ok(())
+    }
+
+    /// Whether a whole source is ignored (its files aren't even discovered)
+    pub fn ignores_source(&self, source: SessionSource) -> bool {
+        self.sources.contains(&source)
+    }
+
+    /// Whether a session file is ignored by its name alone (`<session-id>.jsonl`)
+    pub fn ignores_file(&self, path: &Path) -> bool {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .is_some_and(|stem| self.sessions.iter().any(|id| id == stem))
+    }
+
+    /// Whether a parsed session is ignored by any rule. Content rules see the same
+    /// text a reader of the session can: messages, tool calls and results, and the
+    /// reasoning when it gets indexed.
+    pub fn ignores_session(&self, session: &Session, include_reasoning: bool) -> bool {
+        self.ignores_source(session.source)
+            || self.sessions.contains(&session.id)
+            || self.cwd.iter().any(|glob| cwd_matches(glob, &session.cwd))
+            || (!self.content.is_empty()
+                && session.messages.iter().any(|m| {
+                    searchable_texts(m, include_reasoning)
+                        .iter()
+                        .any(|text| self.content.iter().any(|re| re.is_match(text)))
+                }))
+    }
+
+    /// Whether a project is hidden in private mode
+    pub fn is_private(&self, cwd: &str) -> bool {
+        self.private.iter().any(|glob| cwd_matches(glob, cwd))
+    }
+
+    /// Identifies the rules that decide what gets indexed (0 when there are none),
+    /// so that changing them purges sessions indexed under the old rules. Content rules
+    /// also see reasoning when it's indexed, so that counts too. It's kept in the index
+    /// state, so it's hashed with FNV-1a (the standard library's hasher can change
+    /// between Rust releases).
+    pub fn fingerprint(&self, include_reasoning: bool) -> u64 {
+        if self.cwd.is_empty()
+            && self.sources.is_empty()
+            && self.sessions.is_empty()
+            && self.content.is_empty()
+        {
+            return 0;
+        }
+        let mut rules = Vec::new();
+        let mut add = |kind: &str, rule: &str| {
+            rules.extend_from_slice(kind.as_bytes());
+            rules.push(b':');
+            rules.extend_from_slice(rule.as_bytes());
+            // Never part of UTF-8 text, so rules can't run together
+            rules.push(0xff);
+        };
+        self.cwd.iter().for_each(|glob| add("cwd", glob));
+        self.sources.iter().for_each(|source| add("source", source.as_str()));
+        self.sessions.iter().for_each(|id| add("session", id));
+        self.content.iter().for_each(|regex| add("content", regex.as_str()));
+        if include_reasoning && !self.content.is_empty() {
+            add("reasoning", "");
+        }
+        fnv1a(&rules).max(1)
+    }
+}
+
+/// 64-bit FNV-1a hash
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// The texts of a message that content rules match against
+fn searchable_texts(message: &Message, include_reasoning: bool) -> Vec<String> {
+    let mut texts = vec![message.content.clone()];
+    if include_reasoning {
+        texts.extend(message.reasoning.clone());
+    }
+    for block in &message.blocks {
+        match block {
+            ContentBlock::ToolUse { input, .. } => texts.push(input.to_string()),
+            ContentBlock::ToolResult { output, .. } => texts.push(output.clone()),
+            _ => {}
+        }
+    }
+    texts
+}
+
+fn line_context(lineno: usize) -> String {
+    format!(".recallignore line {}", lineno + 1)
+}
+
+/// Expand a leading `~/` to the home directory
+pub fn expand_home(glob: &str) -> String {
+    match (glob.strip_prefix("~/"), paths::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => glob.to_string(),
+    }
+}
+
+/// Match a working directory against a glob (`*` within a path component, `**` across
+/// components, `?` one character). A glob without wildcards matches the directory and
+/// everything below it.
+pub fn cwd_matches(glob: &str, cwd: &str) -> bool {
+    if cwd.is_empty() {
+        return false;
+    }
+    if !glob.contains(['*', '?']) {
+        let dir = glob.trim_end_matches('/');
+        return cwd == dir
+            || cwd
+                .strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'));
+    }
+    let glob: Vec<char> = glob.chars().collect();
+    let cwd: Vec<char> = cwd.chars().collect();
+    let mut memo = vec![None; (glob.len() + 1) * (cwd.len() + 1)];
+    glob_match(&glob, &cwd, 0, 0, &mut memo)
+}
+
+/// Whether `glob[g..]` matches `text[t..]`. Results are memoized by position, so
+/// repeated wildcards don't backtrack exponentially.
+fn glob_match(glob: &[char], text: &[char], g: usize, t: usize, memo: &mut [Option<bool>]) -> bool {
+    let key = g * (text.len() + 1) + t;
+    if let Some(matched) = memo[key] {
+        return matched;
+    }
+    let matched = match &glob[g..] {
+        [] => t == text.len(),
+        ['*', '*', ..] => (t..=text.len()).any(|i| glob_match(glob, text, g + 2, i, memo)),
+        ['*', ..] => {
+            let mut matched = false;
+            for i in t..=text.len() {
+                if glob_match(glob, text, g + 1, i, memo) {
+                    matched = true;
+                    break;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            matched
+        }
+        ['?', ..] => {
+            text.get(t).is_some_and(|&c| c != '/') && glob_match(glob, text, g + 1, t + 1, memo)
+        }
+        [c, ..] => text.get(t) == Some(c) && glob_match(glob, text, g + 1, t + 1, memo),
+    };
+    memo[key] = Some(matched);
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{Message, Role};
+
+    fn session(id: &str, cwd: &str, text: &str) -> Session {
+        Session::test(id)
+            .with_cwd(cwd)
+            .with_messages(vec![Message::text(Role::User, text, Default::default())])
+    }
+
+    #[test]
+    fn test_cwd_globs() {
+        assert!(cwd_matches("/work/acme", "/work/acme"));
+        assert!(cwd_matches("/work/acme/", "/work/acme/api"));
+        assert!(!cwd_matches("/work/acme", "/work/acme-tools"));
+        assert!(cwd_matches("/work/*/secret", "/work/acme/secret"));
+        assert!(!cwd_matches("/work/*/secret", "/work/a/b/secret"));
+        assert!(cwd_matches("/work/**/secret", "/work/a/b/secret"));
+        assert!(cwd_matches("/clients/**", "/clients/acme/api"));
+        assert!(cwd_matches("/tmp/proj?", "/tmp/proj1"));
+        assert!(!cwd_matches("/work/acme", ""));
+    }
+
+    #[test]
+    fn test_repeated_globstars_finish_quickly() {
+        let glob = format!("/{}x", "**/".repeat(30));
+        let cwd = format!("/{}y", "a/".repeat(40));
+        assert!(!cwd_matches(&glob, &cwd));
+        assert!(cwd_matches(&glob, &cwd.replace('y', "x")));
+    }
+
+    #[test]
+    fn test_ignore_file_syntax() {
+        let file = "# client work\n/work/acme\nsource: opencode\nsession:abc-123\ncontent:(?i)project falcon\nprivate:/work/side\n";
+        let rules = IgnoreRules::from_config(&IgnoreConfig::default(), file).unwrap();
+        assert!(rules.ignores_session(&session("s1", "/work/acme/api", "hi"), false));
+        assert!(rules.ignores_session(&session("abc-123", "/home", "hi"), false));
+        assert!(rules.ignores_session(&session("s2", "/home", "About Project Falcon"), false));
+        assert!(!rules.ignores_session(&session("s3", "/home", "hello"), false));
+        assert!(rules.ignores_source(SessionSource::OpenCode));
+        assert!(rules.ignores_file(Path::new("/x/abc-123.jsonl")));
+        assert!(rules.is_private("/work/side/app"));
+        assert!(!rules.is_private("/work/acme"));
+    }
+
+    #[test]
+    fn test_content_rules_see_tools_and_indexed_reasoning() {
+        let rules = IgnoreRules::from_config(&IgnoreConfig::default(), "content:falcon").unwrap();
+        let message = |block| {
+            let session = Session::test("s").with_messages(vec![Message::new(
+                Role::Assistant,
+                vec![
+                    ContentBlock::Text {
+                        text: "ok".to_string(),
+                    },
+                    block,
+                ],
+                Default::default(),
+            )]);
+            (
+                rules.ignores_session(&session, false),
+                rules.ignores_session(&session, true),
+            )
+        };
+        let tool_use = ContentBlock::ToolUse {
+            name: "Bash".to_string(),
+            input: serde_json::json!({"command": "cd falcon"}),
+        };
+        assert_eq!(message(tool_use), (true, true));
+        let tool_result = ContentBlock::ToolResult {
+            output: "falcon/".to_string(),
+            is_error: false,
+        };
+        assert_eq!(message(tool_result), (true, true));
+        let reasoning = ContentBlock::Reasoning {
+            text: "this is about falcon".to_string(),
+        };
+        assert_eq!(message(reasoning), (false, true));
+    }
+
+    #[test]
+    fn test_config_rules_and_errors() {
+        let config = IgnoreConfig {
+            sources: vec!["codex".to_string()],
+            ..Default::default()
+        };
+        let rules = IgnoreRules::from_config(&config, "").unwrap();
+        assert!(rules.ignores_source(SessionSource::CodexCli));
+
+        let config = IgnoreConfig {
+            sources: vec!["chatgpt".to_string()],
+            ..Default::default()
+        };
+        assert!(IgnoreRules::from_config(&config, "").is_err());
+        assert!(IgnoreRules::from_config(&IgnoreConfig::default(), "content:(").is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_private_rules() {
+        let none = IgnoreRules::default();
+        assert_eq!(none.fingerprint(true), 0);
+        let private = IgnoreRules::from_config(&IgnoreConfig::default(), "private:/a").unwrap();
+        assert_eq!(private.fingerprint(true), 0);
+        let a = IgnoreRules::from_config(&IgnoreConfig::default(), "/a").unwrap();
+        let b = IgnoreRules::from_config(&IgnoreConfig::default(), "/b").unwrap();
+        assert_ne!(a.fingerprint(false), 0);
+        assert_ne!(a.fingerprint(false), b.fingerprint(false));
+        // Reasoning only matters to content rules
+        assert_eq!(a.fingerprint(false), a.fingerprint(true));
+        let content = IgnoreRules::from_config(&IgnoreConfig::default(), "content:secret").unwrap();
+        assert_ne!(content.fingerprint(false), content.fingerprint(true));
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        // Fingerprints are stored in the index state: changing how they're computed
+        // rebuilds every index
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        let rules = IgnoreRules::from_config(&IgnoreConfig::default(), "/a").unwrap();
+        assert_eq!(rules.fingerprint(false), fnv1a(b"cwd:/a\xff"));
+    }
+}
@@ -0,0 +1,377 @@
+//! Encryption at rest for the index cache: a Tantivy `Directory` that seals every
+//! file with XChaCha20-Poly1305, under a key derived from a passphrase or keyfile
+
+use crate::config::EncryptionConfig;
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tantivy::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use tantivy::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, MmapDirectory, OwnedBytes,
+    TerminatingWrite, WatchCallback, WatchHandle, WritePtr,
+};
+
+/// Passphrase for the encrypted index (CLI mode)
+pub const KEY_ENV: &str = "RECALL_INDEX_KEY";
+/// Path of a keyfile for the encrypted index (CLI mode)
+pub const KEYFILE_ENV: &str = "RECALL_INDEX_KEYFILE";
+
+/// Header of every encrypted file, followed by the nonce and the ciphertext
+const MAGIC: &[u8] = b"RECALLENC1";
+const NONCE_LEN: usize = 24;
+/// Sealed in `key.json` to tell a wrong passphrase from a corrupted index
+const CHECK_PLAINTEXT: &[u8] = b"recall index key";
+
+/// Key that encrypts the index and its state
+#[derive(Clone)]
+pub struct IndexKey {
+    cipher: Arc<XChaCha20Poly1305>,
+}
+
+impl std::fmt::Debug for IndexKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IndexKey(..)")
+    }
+}
+
+/// Salt and key check for an encrypted index (stored next to it, in plaintext)
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    salt: Vec<u8>,
+    check: Vec<u8>,
+}
+
+impl IndexKey {
+    fn derive(secret: &[u8], salt: &[u8]) -> Result<Self> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(secret, salt, &mut key)
+            .map_err(|e| anyhow::anyhow!("Failed to derive index key: {}", e))?;
+        Ok(Self {
+            cipher: Arc::new(XChaCha20Poly1305::new(&key.into())),
+        })
+    }
+
+    /// Unlock the encrypted index at `index_path` with a passphrase or keyfile contents.
+    ///
+    /// The first time, this sets up encryption and discards any plaintext index
+    /// (it's only a cache, and gets rebuilt encrypted).
+    pub fn unlock(index_path: &Path, secret: &[u8]) -> Result<Self> {
+        let key_path = key_file_path(index_path);
+        if key_path.exists() {
+            let content = std::fs::read_to_string(&key_path).context("Failed to read key file")?;
+            let key_file: KeyFile =
+                serde_json::from_str(&content).context("Failed to parse key file")?;
+            let key = Self::derive(secret, &key_file.salt)?;
+            match key.open(&key_file.check) {
+                Ok(check) if check == CHECK_PLAINTEXT => Ok(key),
+                _ => anyhow::bail!("Wrong passphrase or keyfile for the encrypted index"),
+            }
+        } else {
+            discard_cache(index_path)?;
+            let mut salt = vec![0u8; 16];
+            OsRng.fill_bytes(&mut salt);
+            let key = Self::derive(secret, &salt)?;
+            let key_file = KeyFile {
+                check: key.seal(CHECK_PLAINTEXT)?,
+                salt,
+            };
+            std::fs::create_dir_all(index_path)?;
+            std::fs::write(&key_path, serde_json::to_string_pretty(&key_file)?)
+                .context("Failed to write key file")?;
+            Ok(key)
+        }
+    }
+
+    /// Encrypt data (a random nonce is stored with it)
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt"))?;
+        let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt data produced by `seal`
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        let body = sealed
+            .strip_prefix(MAGIC)
+            .filter(|b| b.len() >= NONCE_LEN)
+            .ok_or_else(|| anyhow::anyhow!("Not an encrypted recall file"))?;
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt (wrong key or corrupted file)"))
+    }
+}
+
+/// Where the salt and key check of the index at `index_path` are kept
+fn key_file_path(index_path: &Path) -> PathBuf {
+    index_path.with_file_name("key.json")
+}
+
+/// Whether encryption has been set up for the index at `index_path`
+pub fn is_set_up(index_path: &Path) -> bool {
+    key_file_path(index_path).exists()
+}
+
+/// Remove the index and its state (they're rebuilt from the session files)
+fn discard_cache(index_path: &Path) -> Result<()> {
+    if index_path.exists() {
+        std::fs::remove_dir_all(index_path).context("Failed to remove index")?;
+    }
+    let state_path = index_path.with_file_name("state.json");
+    if state_path.exists() {
+        std::fs::remove_file(state_path).context("Failed to remove index state")?;
+    }
+    Ok(())
+}
+
+/// Secret for the encrypted index from the environment or the configured keyfile
+pub fn secret_from_env(config: &EncryptionConfig) -> Result<Option<Vec<u8>>> {
+    if let Ok(passphrase) = std::env::var(KEY_ENV) {
+        return Ok(Some(passphrase.into_bytes()));
+    }
+    let keyfile = std::env::var_os(KEYFILE_ENV)
+        .map(PathBuf::from)
+        .or_else(|| config.keyfile.clone());
+    match keyfile {
+        Some(path) => std::fs::read(&path)
+            .with_context(|| format!("Failed to read keyfile {}", path.display()))
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Get the key for the index at `index_path` as configured (`None` when encryption is off).
+///
+/// The secret comes from the environment or keyfile, else from `passphrase`. Turning
+/// encryption off discards the encrypted index, so it gets rebuilt in plaintext.
+pub fn index_key(
+    config: &EncryptionConfig,
+    index_path: &Path,
+    passphrase: Option<&str>,
+) -> Result<Option<IndexKey>> {
+    if !config.enabled {
+        if is_set_up(index_path) {
+            discard_cache(index_path)?;
+            std::fs::remove_file(key_file_path(index_path)).context("Failed to remove key file")?;
+        }
+        return Ok(None);
+    }
+    let secret = match (secret_from_env(config)?, passphrase) {
+        (Some(secret), _) => secret,
+        (None, Some(passphrase)) => passphrase.as_bytes().to_vec(),
+        (None, None) => anyhow::bail!(
+            "The index is encrypted: set {} to its passphrase, or {} to a keyfile",
+            KEY_ENV,
+            KEYFILE_ENV
+        ),
+    };
+    IndexKey::unlock(index_path, &secret).map(Some)
+}
+
+/// A Tantivy directory whose files are encrypted on disk. Locking, deletion and
+/// watching are left to an `MmapDirectory` over the same path.
+///
+/// Each file is sealed as a whole, so it can't be memory-mapped: opening the index
+/// reads and decrypts every segment file into memory, and writing a segment buffers
+/// it in memory until it's complete. Memory use grows with the size of the index
+/// (roughly the size of the cache directory).
+#[derive(Clone, Debug)]
+pub struct EncryptedDirectory {
+    root: PathBuf,
+    inner: MmapDirectory,
+    key: IndexKey,
+}
+
+impl EncryptedDirectory {
+    pub fn open(root: &Path, key: IndexKey) -> Result<Self> {
+        let inner = MmapDirectory::open(root).context("Failed to open index directory")?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            inner,
+            key,
+        })
+    }
+
+    /// Read and decrypt a whole file (freshly created files are still empty)
+    fn read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let sealed = std::fs::read(self.root.join(path)).map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                OpenReadError::FileDoesNotExist(path.to_path_buf())
+            } else {
+                OpenReadError::wrap_io_error(e, path.to_path_buf())
+            }
+        })?;
+        if sealed.is_empty() {
+            return Ok(sealed);
+        }
+        self.key.open(&sealed).map_err(|e| {
+            OpenReadError::wrap_io_error(
+                io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+                path.to_path_buf(),
+            )
+        })
+    }
+}
+
+impl Directory for EncryptedDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        Ok(Arc::new(OwnedBytes::new(self.read(path)?)))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.inner.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.inner.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let full_path = self.root.join(path);
+        // Create the (empty) file right away: the directory is write-once
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&full_path)
+            .map_err(|e| {
+                if e.kind() == io::ErrorKind::AlreadyExists {
+                    OpenWriteError::FileAlreadyExists(path.to_path_buf())
+                } else {
+                    OpenWriteError::wrap_io_error(e, path.to_path_buf())
+                }
+            })?;
+        Ok(BufWriter::new(Box::new(EncryptedWriter {
+            path: full_path,
+            key: self.key.clone(),
+            data: Vec::new(),
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let sealed = self.key.seal(data).map_err(io::Error::other)?;
+        self.inner.atomic_write(path, &sealed)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.inner.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.inner.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> tantivy::Result<WatchHandle> {
+        self.inner.watch(watch_callback)
+    }
+}
+
+/// Buffers a file in memory, and writes it encrypted once it's complete
+struct EncryptedWriter {
+    path: PathBuf,
+    key: IndexKey,
+    data: Vec<u8>,
+}
+
+impl Write for EncryptedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    /// Nothing is written before `terminate`: the file is sealed as a whole
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl TerminatingWrite for EncryptedWriter {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        let sealed = self.key.seal(&self.data).map_err(io::Error::other)?;
+        let mut file = std::fs::File::create(&self.path)?;
+        file.write_all(&sealed)?;
+        file.sync_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = IndexKey::unlock(&dir.path().join("index"), b"hunter2").unwrap();
+        let sealed = key.seal(b"secret conversation").unwrap();
+        assert!(!sealed
+            .windows(b"secret".len())
+            .any(|w| w == b"secret"));
+        assert_eq!(key.open(&sealed).unwrap(), b"secret conversation");
+        assert!(key.open(b"plaintext").is_err());
+    }
+
+    #[test]
+    fn test_unlock_checks_the_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = dir.path().join("index");
+        let key = IndexKey::unlock(&index_path, b"hunter2").unwrap();
+        let sealed = key.seal(b"data").unwrap();
+
+        assert!(IndexKey::unlock(&index_path, b"wrong").is_err());
+        let again = IndexKey::unlock(&index_path, b"hunter2").unwrap();
+        assert_eq!(again.open(&sealed).unwrap(), b"data");
+    }
+
+    #[test]
+    fn test_files_are_sealed_once_complete() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = dir.path().join("index");
+        let key = IndexKey::unlock(&index_path, b"hunter2").unwrap();
+        let directory = EncryptedDirectory::open(&index_path, key).unwrap();
+
+        let path = Path::new("segment.idx");
+        let mut writer = directory.open_write(path).unwrap();
+        writer.write_all(b"postings").unwrap();
+        writer.flush().unwrap();
+        assert!(std::fs::read(index_path.join(path)).unwrap().is_empty());
+        writer.write_all(b" and more").unwrap();
+        writer.terminate().unwrap();
+
+        let handle = directory.get_file_handle(path).unwrap();
+        assert_eq!(
+            handle.read_bytes(0..handle.len()).unwrap().as_slice(),
+            b"postings and more"
+        );
+    }
+
+    #[test]
+    fn test_first_unlock_discards_plaintext_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = dir.path().join("index");
+        std::fs::create_dir_all(&index_path).unwrap();
+        std::fs::write(index_path.join("meta.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("state.json"), "{}").unwrap();
+
+        IndexKey::unlock(&index_path, b"hunter2").unwrap();
+        assert!(!index_path.join("meta.json").exists());
+        assert!(!dir.path().join("state.json").exists());
+        assert!(is_set_up(&index_path));
+    }
+}
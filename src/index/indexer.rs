@@ -2,6 +2,7 @@
 
 use super::state::IndexState;
 use super::SessionIndex;
+use crate::ignore::IgnoreRules;
 use crate::parser;
 use anyhow::Result;
 use std::path::PathBuf;
@@ -19,9 +20,10 @@ pub type ProgressCallback = Box<dyn FnMut(IndexProgress) + Send>;
 /// Callback for notifying that the index should be reloaded
 pub type ReloadCallback = Box<dyn FnMut() + Send>;
 
-/// Discovers session files and sorts them by modification time (most recent first)
-pub fn discover_and_sort_files() -> Vec<PathBuf> {
-    let mut files = parser::discover_session_files();
+/// Discovers session files not excluded by the ignore rules,
+/// sorted by modification time (most recent first)
+pub fn discover_and_sort_files(ignore: &IgnoreRules) -> Vec<PathBuf> {
+    let mut files = parser::discover_session_files(ignore);
     files.sort_by(|a, b| {
         let mtime_a = std::fs::metadata(a)
             .and_then(|m| m.modified())
@@ -34,6 +36,29 @@ pub fn discover_and_sort_files() -> Vec<PathBuf> {
     files
 }
 
+/// Remove every indexed session if the ignore rules changed since they were indexed,
+/// so sessions matching a new rule leave the index (the others get reindexed).
+///
+/// Returns whether anything was removed.
+pub fn purge_if_ignore_rules_changed(index: &SessionIndex, state: &mut IndexState) -> Result<bool> {
+    let fingerprint = index.ignore_rules().fingerprint(index.include_reasoning());
+    if state.ignore_fingerprint == fingerprint {
+        return Ok(false);
+    }
+    state.ignore_fingerprint = fingerprint;
+    if state.indexed_files.is_empty() {
+        return Ok(false);
+    }
+
+    let mut writer = index.writer()?;
+    for file_path in state.indexed_files.keys() {
+        index.delete_session(&mut writer, file_path);
+    }
+    writer.commit()?;
+    state.indexed_files.clear();
+    Ok(true)
+}
+
 /// Index a batch of files, calling progress callbacks as work proceeds.
 ///
 /// - `on_progress`: Called every 50 files with current progress
@@ -58,7 +83,10 @@ pub fn index_files(
         // Parse and index
         match parser::parse_session_file(file_path) {
             Ok(session) => {
-                if !session.messages.is_empty() {
+                let ignored = index
+                    .ignore_rules()
+                    .ignores_session(&session, index.include_reasoning());
+                if !session.messages.is_empty() && !ignored {
                     let _ = index.index_session(writer, &session);
                 }
                 // Mark as indexed even if empty (so we don't reprocess it)
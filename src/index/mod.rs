@@ -1,9 +1,15 @@
+mod encrypted;
 mod indexer;
 mod schema;
 mod state;
 mod sync;
 
-pub use indexer::{discover_and_sort_files, index_files, IndexProgress};
-pub use schema::SessionIndex;
+pub use indexer::{
+    discover_and_sort_files, index_files, purge_if_ignore_rules_changed, IndexProgress,
+};
+pub use encrypted::{index_key, is_set_up, secret_from_env, IndexKey, KEYFILE_ENV, KEY_ENV};
+pub use schema::{
+    default_index_path, BranchFilter, PageKey, SearchPage, SessionIndex, SortOrder, SubagentFilter,
+};
 pub use state::IndexState;
 pub use sync::ensure_index_fresh;
@@ -1,57 +1,339 @@
-use crate::session::{SearchResult, Session, SessionSource};
+use super::encrypted::{index_key, EncryptedDirectory, IndexKey};
+use crate::config::{Config, RankingConfig};
+use crate::ignore::IgnoreRules;
+use crate::redact::Redactor;
+use crate::session::{ScoreBreakdown, SearchResult, Session, SessionSource};
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
-use tantivy::collector::TopDocs;
-use tantivy::query::{BooleanQuery, BoostQuery, Occur, PhraseQuery, Query, QueryParser, TermQuery};
+use std::ops::Bound;
+use tantivy::collector::{Count, DocSetCollector, TopDocs};
+use tantivy::query::{
+    BooleanQuery, BoostQuery, MoreLikeThisQuery, Occur, PhraseQuery, Query, QueryParser,
+    RangeQuery, RegexQuery, TermQuery, TermSetQuery,
+};
 use tantivy::schema::*;
 use tantivy::snippet::SnippetGenerator;
-use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy};
+use tantivy::directory::{Directory, MmapDirectory};
+use tantivy::{doc, Index, IndexReader, IndexSettings, IndexWriter, ReloadPolicy};
 
 /// Get the default cache directory for the index
 pub fn default_index_path() -> PathBuf {
-    std::env::var("RECALL_HOME_OVERRIDE")
-        .map(|h| PathBuf::from(h).join(".cache").join("recall").join("index"))
-        .unwrap_or_else(|_| {
-            dirs::cache_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("recall")
-                .join("index")
-        })
+    crate::paths::cache_dir().join("index")
+}
+
+/// `doc_type` of the one summary document per session (metadata only)
+const DOC_TYPE_SESSION: &str = "session";
+/// `doc_type` of a message document (searchable content)
+const DOC_TYPE_MESSAGE: &str = "message";
+
+/// Order of search results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Relevance combined with recency (see `RankingConfig`)
+    #[default]
+    Relevance,
+    /// Most recently active sessions first
+    Recent,
+    /// Least recently active sessions first
+    Oldest,
+}
+
+/// Which sessions to include, by whether they're subagent transcripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubagentFilter {
+    /// Both top-level sessions and subagents
+    #[default]
+    Include,
+    /// Top-level sessions only
+    Exclude,
+    /// Subagents only
+    Only,
+}
+
+impl SubagentFilter {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "include" => Some(SubagentFilter::Include),
+            "exclude" => Some(SubagentFilter::Exclude),
+            "only" => Some(SubagentFilter::Only),
+            _ => None,
+        }
+    }
+
+    /// Check whether a session passes the filter
+    pub fn matches(&self, session: &Session) -> bool {
+        match self {
+            SubagentFilter::Include => true,
+            SubagentFilter::Exclude => !session.is_subagent,
+            SubagentFilter::Only => session.is_subagent,
+        }
+    }
+}
+
+/// Which sessions to include, by the git branch they were on: a branch name, or a
+/// prefix ending in `*` (`feature/*`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchFilter(String);
+
+impl BranchFilter {
+    pub fn new(pattern: &str) -> Self {
+        Self(pattern.to_string())
+    }
+
+    /// Take `branch:<name>` terms out of a search query, returning the rest of the
+    /// query and a filter for the (last) branch named
+    pub fn from_query(query: &str) -> (String, Option<Self>) {
+        let mut filter = None;
+        let mut rest = Vec::new();
+        for word in query.split_whitespace() {
+            match word.strip_prefix("branch:") {
+                Some(branch) if !branch.is_empty() => filter = Some(Self::new(branch)),
+                _ => rest.push(word),
+            }
+        }
+        if filter.is_none() {
+            return (query.to_string(), None);
+        }
+        (rest.join(" "), filter)
+    }
+
+    /// Check whether a session passes the filter
+    pub fn matches(&self, session: &Session) -> bool {
+        let Some(branch) = session.git_branch.as_deref() else {
+            return false;
+        };
+        match self.0.strip_suffix('*') {
+            Some(prefix) => branch.starts_with(prefix),
+            None => branch == self.0,
+        }
+    }
+}
+
+impl SortOrder {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "relevance" => Some(SortOrder::Relevance),
+            "recent" => Some(SortOrder::Recent),
+            "oldest" => Some(SortOrder::Oldest),
+            _ => None,
+        }
+    }
+}
+
+/// One page of session-level results
+#[derive(Debug, Default)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Sort key of each result, to continue after it
+    pub keys: Vec<PageKey>,
+    /// Where the next page starts: after the last result (None if this is the last page)
+    pub next: Option<PageKey>,
+}
+
+/// Position of a session in results ordered by a key that's unique per session, so
+/// pages continue after it without repeating or skipping sessions
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageKey {
+    /// The session with this final score (highest first, then by session ID), with
+    /// recency computed as of `now` so every page ranks alike
+    Score {
+        now: i64,
+        score: f64,
+        session_id: String,
+    },
+    /// The session last active at `timestamp` (in the sort's direction, then by
+    /// session ID)
+    Time { timestamp: i64, session_id: String },
+}
+
+impl PageKey {
+    /// Encode as a page token for the CLI
+    pub fn to_token(&self) -> String {
+        match self {
+            PageKey::Score {
+                now,
+                score,
+                session_id,
+            } => format!("s:{}:{:x}:{}", now, score.to_bits(), session_id),
+            PageKey::Time {
+                timestamp,
+                session_id,
+            } => format!("t:{}:{}", timestamp, session_id),
+        }
+    }
+
+    /// Decode a page token from `to_token`
+    pub fn parse_token(token: &str) -> Option<Self> {
+        let mut parts = token.splitn(4, ':');
+        match parts.next()? {
+            "s" => {
+                let now = parts.next()?.parse().ok()?;
+                let score = f64::from_bits(u64::from_str_radix(parts.next()?, 16).ok()?);
+                let session_id = parts.next()?.to_string();
+                Some(PageKey::Score {
+                    now,
+                    score,
+                    session_id,
+                })
+            }
+            "t" => {
+                let timestamp = parts.next()?.parse().ok()?;
+                let rest: Vec<&str> = parts.collect();
+                Some(PageKey::Time {
+                    timestamp,
+                    session_id: rest.join(":"),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Fast-field columns of each segment, to read which session a matching message
+/// belongs to without loading its document
+struct MessageColumns {
+    session_id: Vec<Option<tantivy::columnar::StrColumn>>,
+    message_index: Vec<tantivy::columnar::Column<u64>>,
+    timestamp: Vec<tantivy::columnar::Column<i64>>,
+}
+
+impl MessageColumns {
+    fn open(searcher: &tantivy::Searcher) -> Result<Self> {
+        let mut columns = Self {
+            session_id: Vec::new(),
+            message_index: Vec::new(),
+            timestamp: Vec::new(),
+        };
+        for segment in searcher.segment_readers() {
+            let fast_fields = segment.fast_fields();
+            columns.session_id.push(fast_fields.str("session_id")?);
+            columns.message_index.push(fast_fields.u64("message_index")?);
+            columns.timestamp.push(fast_fields.i64("timestamp")?);
+        }
+        Ok(columns)
+    }
+
+    /// Session ID of a document
+    fn session_id(&self, doc_addr: tantivy::DocAddress) -> Result<Option<String>> {
+        let segment = doc_addr.segment_ord as usize;
+        let Some(column) = self.session_id.get(segment).and_then(|c| c.as_ref()) else {
+            return Ok(None);
+        };
+        let Some(ord) = column.term_ords(doc_addr.doc_id).next() else {
+            return Ok(None);
+        };
+        let mut session_id = String::new();
+        column.ord_to_str(ord, &mut session_id)?;
+        Ok(Some(session_id))
+    }
+
+    /// Session ID, message index and session timestamp of a message document
+    fn read(&self, doc_addr: tantivy::DocAddress) -> Result<Option<(String, usize, i64)>> {
+        let segment = doc_addr.segment_ord as usize;
+        let Some(session_id) = self.session_id(doc_addr)? else {
+            return Ok(None);
+        };
+        let message_index = self.message_index[segment].first(doc_addr.doc_id).unwrap_or(0);
+        let timestamp = self.timestamp[segment].first(doc_addr.doc_id).unwrap_or(0);
+        Ok(Some((session_id, message_index as usize, timestamp)))
+    }
+}
+
+/// Snippet generators for a query's matches in messages and in their reasoning
+struct Snippets {
+    content: SnippetGenerator,
+    reasoning: SnippetGenerator,
 }
 
 /// Wrapper around Tantivy index for session search
 pub struct SessionIndex {
     index: Index,
     reader: IndexReader,
+    ranking: RankingConfig,
+    /// Whether to index messages' reasoning (see `IndexConfig`)
+    include_reasoning: bool,
+    /// Secrets are redacted before anything is indexed (see `RedactConfig`)
+    redactor: Redactor,
+    /// Sessions kept out of the index (see `IgnoreConfig`)
+    ignore: IgnoreRules,
+    /// Key of an encrypted index (see `EncryptionConfig`)
+    key: Option<IndexKey>,
     #[allow(dead_code)]
     schema: Schema,
     // Field handles
+    doc_type: Field,
     session_id: Field,
     source: Field,
     file_path: Field,
     cwd: Field,
     git_branch: Field,
     timestamp: Field,
+    start_timestamp: Field,
+    active_secs: Field,
+    message_count: Field,
+    first_prompt: Field,
+    title: Field,
+    parent_id: Field,
+    is_subagent: Field,
     content: Field,
+    reasoning: Field,
     message_index: Field,
 }
 
 impl SessionIndex {
-    /// Open existing index or create a new one at the default path
+    /// Open existing index or create a new one at the default path,
+    /// configured from the user's config file
     pub fn open_default() -> Result<Self> {
-        Self::open_or_create(&default_index_path())
+        let config = Config::load()?;
+        let index_path = default_index_path();
+        let key = index_key(&config.encryption, &index_path, None)?;
+        let mut index = Self::open_or_create_with_key(&index_path, key)?;
+        index.configure(&config)?;
+        Ok(index)
+    }
+
+    /// Apply the user's config: ranking weights, what gets indexed, redaction
+    /// and ignore rules (including `~/.recallignore`)
+    pub fn configure(&mut self, config: &Config) -> Result<()> {
+        self.set_ranking(config.ranking.clone());
+        self.set_include_reasoning(config.index.include_reasoning);
+        self.set_redactor(Redactor::from_config(&config.redact)?);
+        self.set_ignore_rules(IgnoreRules::load(&config.ignore)?);
+        Ok(())
     }
 
     /// Open existing index or create a new one
     pub fn open_or_create(index_path: &Path) -> Result<Self> {
+        Self::open_or_create_with_key(index_path, None)
+    }
+
+    /// Open existing index or create a new one, encrypted with `key` if given
+    pub fn open_or_create_with_key(index_path: &Path, key: Option<IndexKey>) -> Result<Self> {
         std::fs::create_dir_all(index_path)?;
 
         let schema = Self::build_schema();
+        let directory = |key: &Option<IndexKey>| -> Result<Box<dyn Directory>> {
+            Ok(match key {
+                Some(key) => Box::new(EncryptedDirectory::open(index_path, key.clone())?),
+                None => Box::new(MmapDirectory::open(index_path)?),
+            })
+        };
 
         let index = if index_path.join("meta.json").exists() {
-            Index::open_in_dir(index_path).context("Failed to open existing index")?
+            let index = Index::open(directory(&key)?).context("Failed to open existing index")?;
+            if index.schema() == schema {
+                index
+            } else {
+                // Built by an older version: start over (the index state is versioned too,
+                // so every session gets reindexed)
+                drop(index);
+                std::fs::remove_dir_all(index_path)?;
+                std::fs::create_dir_all(index_path)?;
+                Index::create(directory(&key)?, schema.clone(), IndexSettings::default())
+                    .context("Failed to create new index")?
+            }
         } else {
-            Index::create_in_dir(index_path, schema.clone())
+            Index::create(directory(&key)?, schema.clone(), IndexSettings::default())
                 .context("Failed to create new index")?
         };
 
@@ -64,13 +346,27 @@ impl SessionIndex {
         Ok(Self {
             index,
             reader,
+            ranking: RankingConfig::default(),
+            include_reasoning: false,
+            redactor: Redactor::default(),
+            ignore: IgnoreRules::default(),
+            key,
+            doc_type: schema.get_field("doc_type").unwrap(),
             session_id: schema.get_field("session_id").unwrap(),
             source: schema.get_field("source").unwrap(),
             file_path: schema.get_field("file_path").unwrap(),
             cwd: schema.get_field("cwd").unwrap(),
             git_branch: schema.get_field("git_branch").unwrap(),
             timestamp: schema.get_field("timestamp").unwrap(),
+            start_timestamp: schema.get_field("start_timestamp").unwrap(),
+            active_secs: schema.get_field("active_secs").unwrap(),
+            message_count: schema.get_field("message_count").unwrap(),
+            first_prompt: schema.get_field("first_prompt").unwrap(),
+            title: schema.get_field("title").unwrap(),
+            parent_id: schema.get_field("parent_id").unwrap(),
+            is_subagent: schema.get_field("is_subagent").unwrap(),
             content: schema.get_field("content").unwrap(),
+            reasoning: schema.get_field("reasoning").unwrap(),
             message_index: schema.get_field("message_index").unwrap(),
             schema,
         })
@@ -79,22 +375,41 @@ impl SessionIndex {
     fn build_schema() -> Schema {
         let mut builder = Schema::builder();
 
-        // Stored metadata fields
-        builder.add_text_field("session_id", STRING | STORED);
+        // Document kind: one "session" summary per session, plus one "message" per message
+        builder.add_text_field("doc_type", STRING);
+
+        // Stored metadata fields (file_path is on every document, for deletion; session_id
+        // is a fast field to group matches by session without loading their documents)
+        builder.add_text_field("session_id", STRING | STORED | FAST);
         builder.add_text_field("source", STRING | STORED);
         builder.add_text_field("file_path", STRING | STORED);
         builder.add_text_field("cwd", STRING | STORED);
         builder.add_text_field("git_branch", STRING | STORED);
 
-        // Timestamp for recency sorting (stored as i64 unix timestamp)
+        // Last activity timestamp for recency sorting (stored as i64 unix timestamp).
+        // Messages carry their session's, so matches can be sorted by time too.
         builder.add_i64_field("timestamp", INDEXED | STORED | FAST);
 
+        // Session summary fields (display only, not searchable)
+        builder.add_i64_field("start_timestamp", STORED);
+        builder.add_i64_field("active_secs", STORED);
+        builder.add_u64_field("message_count", STORED);
+        builder.add_text_field("first_prompt", STORED);
+        builder.add_text_field("title", STORED);
+
+        // Session this one continues (empty for thread roots), for thread lookups
+        builder.add_text_field("parent_id", STRING | STORED);
+        builder.add_bool_field("is_subagent", STORED);
+
         // Message index within the session (for match-recency)
-        builder.add_u64_field("message_index", STORED);
+        builder.add_u64_field("message_index", STORED | FAST);
 
         // Searchable content field
         builder.add_text_field("content", TEXT | STORED);
 
+        // Searchable reasoning field (only filled when reasoning indexing is enabled)
+        builder.add_text_field("reasoning", TEXT | STORED);
+
         builder.build()
     }
 
@@ -105,22 +420,51 @@ impl SessionIndex {
             .context("Failed to create index writer")
     }
 
-    /// Index a single session (all its messages)
+    /// Index a single session (a summary document plus all its messages)
     pub fn index_session(&self, writer: &mut IndexWriter, session: &Session) -> Result<()> {
-        let timestamp_secs = session.timestamp.timestamp();
+        let file_path = session.file_path.to_string_lossy().to_string();
+        let mut session = session.clone();
+        self.redactor.redact_session(&mut session);
+
+        let first_prompt = session
+            .messages
+            .iter()
+            .find(|m| m.is_user_turn())
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        // Session metadata lives only in the summary document
+        writer.add_document(doc!(
+            self.doc_type => DOC_TYPE_SESSION,
+            self.session_id => session.id.clone(),
+            self.source => session.source.as_str(),
+            self.file_path => file_path.clone(),
+            self.cwd => session.cwd.clone(),
+            self.git_branch => session.git_branch.clone().unwrap_or_default(),
+            self.timestamp => session.timestamp.timestamp(),
+            self.start_timestamp => session.start_timestamp.timestamp(),
+            self.active_secs => session.active_duration.num_seconds(),
+            self.message_count => session.turn_count() as u64,
+            self.first_prompt => first_prompt,
+            self.title => session.title.clone(),
+            self.parent_id => session.parent_id.clone().unwrap_or_default(),
+            self.is_subagent => session.is_subagent,
+        ))?;
 
         // Index each message separately for match-recency ranking
         for (idx, message) in session.messages.iter().enumerate() {
-            let doc = doc!(
+            let mut doc = doc!(
+                self.doc_type => DOC_TYPE_MESSAGE,
                 self.session_id => session.id.clone(),
-                self.source => session.source.as_str(),
-                self.file_path => session.file_path.to_string_lossy().to_string(),
-                self.cwd => session.cwd.clone(),
-                self.git_branch => session.git_branch.clone().unwrap_or_default(),
-                self.timestamp => timestamp_secs,
+                self.file_path => file_path.clone(),
                 self.message_index => idx as u64,
+                self.timestamp => session.timestamp.timestamp(),
                 self.content => message.content.clone(),
             );
+            if self.include_reasoning {
+                if let Some(reasoning) = &message.reasoning {
+                    doc.add_text(self.reasoning, reasoning);
+                }
+            }
             writer.add_document(doc)?;
         }
 
@@ -141,15 +485,68 @@ impl SessionIndex {
         self.reader.reload().context("Failed to reload reader")
     }
 
+    /// Set the weights used to rank search results
+    pub fn set_ranking(&mut self, ranking: RankingConfig) {
+        self.ranking = ranking;
+    }
+
+    /// Set whether sessions indexed from now on include their reasoning
+    pub fn set_include_reasoning(&mut self, include_reasoning: bool) {
+        self.include_reasoning = include_reasoning;
+    }
+
+    /// Whether sessions get indexed with their reasoning
+    pub fn include_reasoning(&self) -> bool {
+        self.include_reasoning
+    }
+
+    /// Set the redactor applied to sessions indexed from now on
+    pub fn set_redactor(&mut self, redactor: Redactor) {
+        self.redactor = redactor;
+    }
+
+    /// Set the rules for sessions kept out of the index
+    pub fn set_ignore_rules(&mut self, ignore: IgnoreRules) {
+        self.ignore = ignore;
+    }
+
+    /// Key of the encrypted index, if encryption is on (the index state uses it too)
+    pub fn key(&self) -> Option<&IndexKey> {
+        self.key.as_ref()
+    }
+
+    /// Rules for sessions kept out of the index, and projects hidden in private mode
+    pub fn ignore_rules(&self) -> &IgnoreRules {
+        &self.ignore
+    }
+
     /// Search for sessions matching the query
     /// Returns results grouped by session, ranked by match-recency
     pub fn search(&self, query_str: &str, limit: usize) -> Result<Vec<SearchResult>> {
-        if query_str.trim().is_empty() {
-            return Ok(Vec::new());
+        Ok(self
+            .search_page(query_str, SortOrder::Relevance, None, limit)?
+            .results)
+    }
+
+    /// Search for a page of sessions matching the query, in the given order, starting
+    /// after the session at `after` (the `next` key of the previous page).
+    /// `limit` counts sessions, not messages.
+    pub fn search_page(
+        &self,
+        query_str: &str,
+        sort: SortOrder,
+        after: Option<&PageKey>,
+        limit: usize,
+    ) -> Result<SearchPage> {
+        if query_str.trim().is_empty() || limit == 0 {
+            return Ok(SearchPage::default());
         }
 
         let searcher = self.reader.searcher();
-        let query_parser = QueryParser::for_index(&self.index, vec![self.content]);
+        // Reasoning matches count, but for less than matches in the messages themselves
+        let mut query_parser =
+            QueryParser::for_index(&self.index, vec![self.content, self.reasoning]);
+        query_parser.set_field_boost(self.reasoning, self.ranking.reasoning_weight);
 
         let base_query = query_parser
             .parse_query(query_str)
@@ -167,7 +564,8 @@ impl SessionIndex {
 
             if terms.len() > 1 {
                 let phrase_query = PhraseQuery::new_with_offset(terms);
-                let boosted_phrase = BoostQuery::new(Box::new(phrase_query), 10.0);
+                let boosted_phrase =
+                    BoostQuery::new(Box::new(phrase_query), self.ranking.phrase_boost);
 
                 // Combine: phrase (boosted) OR terms
                 Box::new(BooleanQuery::new(vec![
@@ -182,231 +580,446 @@ impl SessionIndex {
         };
 
         // Create snippet generator from the query - Tantivy knows what terms matched
-        let mut snippet_generator =
-            SnippetGenerator::create(&searcher, &*query, self.content)?;
-        snippet_generator.set_max_num_chars(200);
-
-        // Get more results than limit to group by session
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit * 10))?;
-
-        // Group by session, keeping track of the highest-scoring message per session
-        let mut session_results: std::collections::HashMap<String, (f32, SearchResult)> =
-            std::collections::HashMap::new();
-
-        for (score, doc_addr) in top_docs {
-            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
-
-            let session_id = doc
-                .get_first(self.session_id)
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let source_str = doc
-                .get_first(self.source)
-                .and_then(|v| v.as_str())
-                .unwrap_or("claude");
-
-            let source = SessionSource::parse(source_str).unwrap_or(SessionSource::ClaudeCode);
-
-            let file_path = doc
-                .get_first(self.file_path)
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let cwd = doc
-                .get_first(self.cwd)
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let git_branch = doc
-                .get_first(self.git_branch)
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string())
-                .filter(|s| !s.is_empty());
+        let mut snippets = Snippets {
+            content: SnippetGenerator::create(&searcher, &*query, self.content)?,
+            reasoning: SnippetGenerator::create(&searcher, &*query, self.reasoning)?,
+        };
+        snippets.content.set_max_num_chars(200);
+        snippets.reasoning.set_max_num_chars(200);
 
-            let timestamp_secs = doc
-                .get_first(self.timestamp)
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
+        let order = match sort {
+            SortOrder::Relevance => None,
+            SortOrder::Recent => Some(tantivy::Order::Desc),
+            SortOrder::Oldest => Some(tantivy::Order::Asc),
+        };
+        if let Some(order) = order {
+            return self.search_page_by_time(&searcher, &*query, &snippets, order, after, limit);
+        }
 
-            let message_index = doc
-                .get_first(self.message_index)
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as usize;
+        // Recency is computed as of the first page's search, so every page ranks alike
+        let now = match after {
+            None => chrono::Utc::now().timestamp(),
+            Some(PageKey::Score { now, .. }) => *now,
+            Some(PageKey::Time { .. }) => anyhow::bail!("Page key doesn't match the sort order"),
+        };
 
-            // Use Tantivy's SnippetGenerator for accurate snippet with highlights
-            let tantivy_snippet = snippet_generator.snippet_from_doc(&doc);
-            let fragment = tantivy_snippet.fragment();
-            let highlighted = tantivy_snippet.highlighted();
+        // Every matching message counts towards its session's score, so rank them all
+        // (reading sessions from fast fields; documents are loaded for the page only)
+        let matches = searcher.search(&*query, &Count)?;
+        let top_docs = searcher.search(&*query, &TopDocs::with_limit(matches.max(1)))?;
+        let columns = MessageColumns::open(&searcher)?;
 
-            // Store original fragment for finding match in wrapped text
-            let match_fragment = fragment.to_string();
-            let snippet = fragment.replace('\n', " ");
-            let match_spans: Vec<(usize, usize)> = highlighted
-                .iter()
-                .map(|r| (r.start, r.end))
-                .collect();
+        // Best message of each session: (score, relevance, document, session timestamp)
+        let mut best: std::collections::HashMap<String, (f32, f32, tantivy::DocAddress, i64)> =
+            std::collections::HashMap::new();
+        for (score, doc_addr) in top_docs {
+            let Some((session_id, message_index, timestamp)) = columns.read(doc_addr)? else {
+                continue;
+            };
+            let total = score + self.position_bonus(message_index);
+            match best.entry(session_id) {
+                std::collections::hash_map::Entry::Occupied(mut entry) => {
+                    if total > entry.get().0 {
+                        entry.insert((total, score, doc_addr, timestamp));
+                    }
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert((total, score, doc_addr, timestamp));
+                }
+            }
+        }
 
-            let result = SearchResult {
-                session: Session {
-                    id: session_id.clone(),
-                    source,
-                    file_path: std::path::PathBuf::from(&file_path),
-                    cwd,
-                    git_branch,
-                    timestamp: chrono::DateTime::from_timestamp(timestamp_secs, 0)
-                        .unwrap_or_default(),
-                    messages: Vec::new(), // We don't load all messages for search results
-                },
-                score,
-                matched_message_index: message_index,
-                snippet,
-                match_spans,
-                match_fragment,
+        // Order by final score, then session ID, and continue after the previous page
+        let mut ranked: Vec<(f64, String, f32, tantivy::DocAddress)> = best
+            .into_iter()
+            .map(|(session_id, (total, score, doc_addr, timestamp))| {
+                let (_, multiplier) = self.recency(timestamp, now);
+                ((total as f64) * multiplier, session_id, score, doc_addr)
+            })
+            .filter(|(final_score, session_id, _, _)| match after {
+                Some(PageKey::Score { score, session_id: after_id, .. }) => {
+                    *final_score < *score || (*final_score == *score && session_id > after_id)
+                }
+                _ => true,
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        let has_more = ranked.len() > limit;
+
+        let mut page = SearchPage::default();
+        for (final_score, session_id, score, doc_addr) in ranked.into_iter().take(limit) {
+            let Some(session) = self.load_session(&searcher, &session_id)? else {
+                continue;
             };
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            let mut result = self.message_result(session, &doc, score, &snippets);
+            self.apply_recency(&mut result, now);
+            page.keys.push(PageKey::Score {
+                now,
+                score: final_score,
+                session_id,
+            });
+            page.results.push(result);
+        }
+        if has_more {
+            page.next = page.keys.last().cloned();
+        }
+        Ok(page)
+    }
 
-            // Keep the highest-scoring result for each session
-            // But prefer more recent message indices (higher = more recent)
-            session_results
-                .entry(session_id)
-                .and_modify(|(existing_score, existing_result)| {
-                    // Prefer higher message index (more recent) if scores are similar
-                    let recency_bonus = (message_index as f32) * 0.01;
-                    if score + recency_bonus > *existing_score {
-                        *existing_score = score + recency_bonus;
-                        *existing_result = result.clone();
-                    }
-                })
-                .or_insert((score, result));
+    /// A page of the sessions matching a query, ordered by last activity over the
+    /// whole index (with the timestamp fast field), each with its best match
+    fn search_page_by_time(
+        &self,
+        searcher: &tantivy::Searcher,
+        query: &dyn Query,
+        snippets: &Snippets,
+        order: tantivy::Order,
+        after: Option<&PageKey>,
+        limit: usize,
+    ) -> Result<SearchPage> {
+        let sessions = self.sessions_by_time(searcher, query, order, after, limit.saturating_add(1))?;
+        let has_more = sessions.len() > limit;
+
+        let now = chrono::Utc::now().timestamp();
+        let mut page = SearchPage::default();
+        for (timestamp, session_id, _) in sessions.into_iter().take(limit) {
+            let Some(session) = self.load_session(searcher, &session_id)? else {
+                continue;
+            };
+            // The session's best-scoring match
+            let id_term = tantivy::Term::from_field_text(self.session_id, &session_id);
+            let session_query = BooleanQuery::new(vec![
+                (Occur::Must, query.box_clone()),
+                (Occur::Must, Box::new(TermQuery::new(id_term, IndexRecordOption::Basic))),
+            ]);
+            let Some(&(score, doc_addr)) = searcher
+                .search(&session_query, &TopDocs::with_limit(1))?
+                .first()
+            else {
+                continue;
+            };
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            let mut result = self.message_result(session, &doc, score, snippets);
+            self.apply_recency(&mut result, now);
+            page.keys.push(PageKey::Time {
+                timestamp,
+                session_id,
+            });
+            page.results.push(result);
+        }
+        if has_more {
+            page.next = page.keys.last().cloned();
         }
+        Ok(page)
+    }
 
-        // Sort by combined relevance + recency score
-        // Recency boost: exponential decay with ~7 day half-life
-        let now = chrono::Utc::now().timestamp() as f64;
-        let half_life_secs = 7.0 * 24.0 * 3600.0; // 7 days
+    /// The first `wanted` sessions with documents matching `query` after the session
+    /// at `after`, in time order (ties by session ID), with their timestamps and a
+    /// matching document
+    fn sessions_by_time(
+        &self,
+        searcher: &tantivy::Searcher,
+        query: &dyn Query,
+        order: tantivy::Order,
+        after: Option<&PageKey>,
+        wanted: usize,
+    ) -> Result<Vec<(i64, String, tantivy::DocAddress)>> {
+        let after = match after {
+            None => None,
+            Some(PageKey::Time { timestamp, session_id }) => Some((*timestamp, session_id.as_str())),
+            Some(PageKey::Score { .. }) => anyhow::bail!("Page key doesn't match the sort order"),
+        };
+        // Skip the sessions before the previous page's last one
+        let query: Box<dyn Query> = match after {
+            Some((timestamp, _)) => {
+                let (lower, upper) = match order {
+                    tantivy::Order::Desc => (Bound::Unbounded, Bound::Included(timestamp)),
+                    tantivy::Order::Asc => (Bound::Included(timestamp), Bound::Unbounded),
+                };
+                Box::new(BooleanQuery::new(vec![
+                    (Occur::Must, query.box_clone()),
+                    (Occur::Must, Box::new(RangeQuery::new_i64_bounds("timestamp".to_string(), lower, upper))),
+                ]))
+            }
+            None => query.box_clone(),
+        };
+        let columns = MessageColumns::open(searcher)?;
+
+        let mut sessions: Vec<(i64, String, tantivy::DocAddress)> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut batch = wanted.saturating_mul(10).max(100);
+        let mut fetched = 0;
+        'fetch: loop {
+            let top_docs = searcher.search(
+                &*query,
+                &TopDocs::with_limit(batch)
+                    .and_offset(fetched)
+                    .order_by_fast_field::<i64>("timestamp", order.clone()),
+            )?;
+            let exhausted = top_docs.len() < batch;
+            fetched += top_docs.len();
+
+            for (timestamp, doc_addr) in top_docs {
+                // Past `wanted`, only sessions tied with the last one can still sort before it
+                if sessions.len() >= wanted && sessions.last().is_some_and(|(t, _, _)| *t != timestamp) {
+                    break 'fetch;
+                }
+                let Some(session_id) = columns.session_id(doc_addr)? else {
+                    continue;
+                };
+                if after.is_some_and(|(t, id)| timestamp == t && session_id.as_str() <= id) {
+                    continue;
+                }
+                if seen.insert(session_id.clone()) {
+                    sessions.push((timestamp, session_id, doc_addr));
+                }
+            }
+            if exhausted {
+                break;
+            }
+            batch = batch.saturating_mul(2);
+        }
 
-        let mut results: Vec<_> = session_results.into_values().map(|(_, r)| r).collect();
-        results.sort_by(|a, b| {
-            let age_a = (now - a.session.timestamp.timestamp() as f64).max(0.0);
-            let age_b = (now - b.session.timestamp.timestamp() as f64).max(0.0);
+        // Documents with the same timestamp come in index order: order those by session ID
+        match order {
+            tantivy::Order::Desc => sessions.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1))),
+            tantivy::Order::Asc => sessions.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1))),
+        }
+        sessions.truncate(wanted);
+        Ok(sessions)
+    }
 
-            // Exponential decay: recent sessions get boost up to 2x
-            let recency_a = 1.0 + (-age_a / half_life_secs).exp();
-            let recency_b = 1.0 + (-age_b / half_life_secs).exp();
+    /// Build the result for a session from its matching message document
+    fn message_result(
+        &self,
+        session: Session,
+        doc: &tantivy::TantivyDocument,
+        score: f32,
+        snippets: &Snippets,
+    ) -> SearchResult {
+        let message_index = doc
+            .get_first(self.message_index)
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+
+        // Use Tantivy's SnippetGenerator for accurate snippet with highlights
+        // (from the reasoning if that's all that matched)
+        let mut tantivy_snippet = snippets.content.snippet_from_doc(doc);
+        if tantivy_snippet.highlighted().is_empty() {
+            let reasoning_snippet = snippets.reasoning.snippet_from_doc(doc);
+            if !reasoning_snippet.highlighted().is_empty() {
+                tantivy_snippet = reasoning_snippet;
+            }
+        }
+        let fragment = tantivy_snippet.fragment();
+        let highlighted = tantivy_snippet.highlighted();
+
+        // Store original fragment for finding match in wrapped text
+        let match_fragment = fragment.to_string();
+        let snippet = fragment.replace('\n', " ");
+        let match_spans: Vec<(usize, usize)> = highlighted
+            .iter()
+            .map(|r| (r.start, r.end))
+            .collect();
+
+        let position_bonus = self.position_bonus(message_index);
+
+        SearchResult {
+            session,
+            score: score + position_bonus,
+            score_breakdown: ScoreBreakdown {
+                relevance: score,
+                position_bonus,
+                ..Default::default()
+            },
+            matched_message_index: message_index,
+            snippet,
+            match_spans,
+            match_fragment,
+        }
+    }
 
-            let final_a = (a.score as f64) * recency_a;
-            let final_b = (b.score as f64) * recency_b;
+    /// Prefer higher message index (more recent) if scores are similar
+    fn position_bonus(&self, message_index: usize) -> f32 {
+        (message_index as f32) * self.ranking.message_position_weight
+    }
 
-            final_b.partial_cmp(&final_a).unwrap_or(std::cmp::Ordering::Equal)
-        });
-        results.truncate(limit);
+    /// Age in seconds of a session last active at `timestamp`, and its recency
+    /// multiplier, as of `now`. Recency boost: exponential decay with a configurable
+    /// half-life.
+    fn recency(&self, timestamp: i64, now: i64) -> (f64, f64) {
+        let half_life_secs = self.ranking.recency_half_life_days * 24.0 * 3600.0;
+        let age = ((now - timestamp) as f64).max(0.0);
+        // Exponential decay: recent sessions get boost up to (1 + weight)x
+        let decay = if half_life_secs > 0.0 {
+            (-age / half_life_secs).exp()
+        } else {
+            0.0
+        };
+        (age, 1.0 + self.ranking.recency_weight * decay)
+    }
 
-        Ok(results)
+    /// Combine a result's relevance with the recency of its session, as of `now`
+    fn apply_recency(&self, result: &mut SearchResult, now: i64) {
+        let (age, recency_multiplier) = self.recency(result.session.timestamp.timestamp(), now);
+        result.score_breakdown.age_days = age / (24.0 * 3600.0);
+        result.score_breakdown.recency_multiplier = recency_multiplier;
+        result.score_breakdown.final_score = (result.score as f64) * recency_multiplier;
     }
 
     /// Get recent sessions sorted by timestamp (most recent first)
     pub fn recent(&self, limit: usize) -> Result<Vec<SearchResult>> {
-        use tantivy::collector::TopDocs;
-        use tantivy::query::AllQuery;
+        Ok(self.recent_page(None, limit)?.results)
+    }
+
+    /// Get a page of recent sessions sorted by timestamp (most recent first, ties by
+    /// session ID), starting after the session at `after`
+    pub fn recent_page(&self, after: Option<&PageKey>, limit: usize) -> Result<SearchPage> {
+        if limit == 0 {
+            return Ok(SearchPage::default());
+        }
 
         let searcher = self.reader.searcher();
 
-        // Get all docs sorted by timestamp descending
-        // Fetch many more docs since each session has multiple messages indexed
-        let top_docs = searcher.search(
-            &AllQuery,
-            &TopDocs::with_limit(limit * 100).order_by_fast_field::<i64>("timestamp", tantivy::Order::Desc),
+        // One summary document per session, so no grouping is needed.
+        // Fetch one extra to know whether another page follows.
+        let sessions = self.sessions_by_time(
+            &searcher,
+            &self.doc_type_query(DOC_TYPE_SESSION),
+            tantivy::Order::Desc,
+            after,
+            limit.saturating_add(1),
         )?;
+        let has_more = sessions.len() > limit;
 
-        // Group by session, keeping only the most recent per session
-        let mut session_results: std::collections::HashMap<String, SearchResult> =
-            std::collections::HashMap::new();
-
-        for (_score, doc_addr) in top_docs {
+        let mut page = SearchPage::default();
+        for (timestamp, session_id, doc_addr) in sessions.into_iter().take(limit) {
             let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
 
-            let session_id = doc
-                .get_first(self.session_id)
+            // Use first part of the first prompt as snippet
+            let first_prompt = doc
+                .get_first(self.first_prompt)
                 .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
+                .unwrap_or("");
+            let snippet: String = first_prompt.chars().take(200).collect();
 
-            // Skip if we already have this session
-            if session_results.contains_key(&session_id) {
-                continue;
+            page.results.push(SearchResult {
+                session: self.session_from_doc(&doc),
+                score: 0.0,
+                score_breakdown: ScoreBreakdown::default(),
+                matched_message_index: 0,
+                snippet: snippet.replace('\n', " "),
+                match_spans: Vec::new(),
+                match_fragment: String::new(),
+            });
+            page.keys.push(PageKey::Time {
+                timestamp,
+                session_id,
+            });
+        }
+        if has_more {
+            page.next = page.keys.last().cloned();
+        }
+        Ok(page)
+    }
+
+    /// Find sessions with content similar to the given session
+    /// Builds a more-like-this query from all of the session's messages and
+    /// returns other sessions that pass `keep`, ranked by their best-matching message
+    pub fn related(
+        &self,
+        session_id: &str,
+        limit: usize,
+        keep: impl Fn(&Session) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        let searcher = self.reader.searcher();
+
+        // Gather the content of every message in the source session
+        let term = tantivy::Term::from_field_text(self.session_id, session_id);
+        let session_query = TermQuery::new(term, IndexRecordOption::Basic);
+        let doc_addrs = searcher.search(&session_query, &DocSetCollector)?;
+        if doc_addrs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut values: Vec<OwnedValue> = Vec::new();
+        for doc_addr in doc_addrs {
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            if let Some(text) = doc.get_first(self.content).and_then(|v| v.as_str()) {
+                values.push(OwnedValue::Str(text.to_string()));
             }
+        }
 
-            let source_str = doc
-                .get_first(self.source)
-                .and_then(|v| v.as_str())
-                .unwrap_or("claude");
+        // Ignore terms that appear in a large share of messages (boilerplate). The share
+        // is of the number of sessions, so it doesn't drift as sessions get longer.
+        let sessions = searcher.search(&self.doc_type_query(DOC_TYPE_SESSION), &Count)?;
+        let max_doc_frequency = (sessions as u64 / 4).max(10);
+        let query = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_max_doc_frequency(max_doc_frequency)
+            .with_min_term_frequency(1)
+            .with_min_word_length(3)
+            .with_max_query_terms(25)
+            .with_document_fields(vec![(self.content, values)]);
 
-            let source = SessionSource::parse(source_str).unwrap_or(SessionSource::ClaudeCode);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit * 10))?;
 
-            let file_path = doc
-                .get_first(self.file_path)
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
+        // Group by session, keeping the highest-scoring message per session
+        let mut session_results: std::collections::HashMap<String, SearchResult> =
+            std::collections::HashMap::new();
 
-            let cwd = doc
-                .get_first(self.cwd)
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
+        for (score, doc_addr) in top_docs {
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            let other_id = self.session_id_from_doc(&doc);
 
-            let git_branch = doc
-                .get_first(self.git_branch)
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string())
-                .filter(|s| !s.is_empty());
+            // Skip the source session itself
+            if other_id == session_id {
+                continue;
+            }
+            if session_results
+                .get(&other_id)
+                .is_some_and(|existing| existing.score >= score)
+            {
+                continue;
+            }
+            let Some(session) = self.load_session(&searcher, &other_id)?.filter(|s| keep(s)) else {
+                continue;
+            };
 
-            let timestamp_secs = doc
-                .get_first(self.timestamp)
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
+            let message_index = doc
+                .get_first(self.message_index)
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0) as usize;
 
             let content = doc
                 .get_first(self.content)
                 .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            // Use first part of content as snippet
+                .unwrap_or("");
             let snippet: String = content.chars().take(200).collect();
-            let snippet = snippet.replace('\n', " ");
 
             let result = SearchResult {
-                session: Session {
-                    id: session_id.clone(),
-                    source,
-                    file_path: std::path::PathBuf::from(&file_path),
-                    cwd,
-                    git_branch,
-                    timestamp: chrono::DateTime::from_timestamp(timestamp_secs, 0)
-                        .unwrap_or_default(),
-                    messages: Vec::new(),
+                session,
+                score,
+                score_breakdown: ScoreBreakdown {
+                    relevance: score,
+                    final_score: score as f64,
+                    ..Default::default()
                 },
-                score: 0.0,
-                matched_message_index: 0,
-                snippet,
+                matched_message_index: message_index,
+                snippet: snippet.replace('\n', " "),
                 match_spans: Vec::new(),
                 match_fragment: String::new(),
             };
-
-            session_results.insert(session_id, result);
-
-            if session_results.len() >= limit {
-                break;
-            }
+            session_results.insert(result.session.id.clone(), result);
         }
 
-        // Sort by timestamp descending
         let mut results: Vec<_> = session_results.into_values().collect();
-        results.sort_by(|a, b| b.session.timestamp.cmp(&a.session.timestamp));
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
         results.truncate(limit);
 
         Ok(results)
@@ -415,24 +1028,314 @@ impl SessionIndex {
     /// Look up a session by ID and return its file path
     pub fn get_by_id(&self, session_id: &str) -> Result<Option<PathBuf>> {
         let searcher = self.reader.searcher();
+        Ok(self
+            .load_session(&searcher, session_id)?
+            .map(|session| session.file_path))
+    }
 
-        let term = tantivy::Term::from_field_text(self.session_id, session_id);
-        let query = TermQuery::new(term, IndexRecordOption::Basic);
+    /// Get the sessions whose ID starts with `prefix`, most recent first.
+    /// A session whose ID is exactly `prefix` is the only match.
+    pub fn sessions_by_id_prefix(&self, prefix: &str) -> Result<Vec<Session>> {
+        let searcher = self.reader.searcher();
+        if let Some(session) = self.load_session(&searcher, prefix)? {
+            return Ok(vec![session]);
+        }
+
+        let pattern = format!("{}.*", regex::escape(prefix));
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(self.doc_type_query(DOC_TYPE_SESSION)) as Box<dyn Query>),
+            (
+                Occur::Must,
+                Box::new(RegexQuery::from_pattern(&pattern, self.session_id)?),
+            ),
+        ]);
+
+        let mut sessions = Vec::new();
+        for doc_addr in searcher.search(&query, &DocSetCollector)? {
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            sessions.push(self.session_from_doc(&doc));
+        }
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+        Ok(sessions)
+    }
+
+    /// Get the distinct working directories of indexed sessions
+    pub fn known_cwds(&self) -> Result<Vec<String>> {
+        let searcher = self.reader.searcher();
+        let mut cwds = std::collections::BTreeSet::new();
+        for doc_addr in searcher.search(&self.doc_type_query(DOC_TYPE_SESSION), &DocSetCollector)? {
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            if let Some(cwd) = doc.get_first(self.cwd).and_then(|v| v.as_str()) {
+                cwds.insert(cwd.to_string());
+            }
+        }
+        Ok(cwds.into_iter().collect())
+    }
+
+    /// Get the subagent transcripts run by the given session, oldest first
+    pub fn subagents(&self, session_id: &str) -> Result<Vec<Session>> {
+        let mut children = self.children(session_id)?;
+        children.retain(|s| s.is_subagent);
+        Ok(children)
+    }
+
+    /// Get the sessions linked to the given session as their parent
+    /// (continuations, forks and subagents), oldest first
+    pub fn children(&self, session_id: &str) -> Result<Vec<Session>> {
+        let searcher = self.reader.searcher();
+
+        let parent_term = tantivy::Term::from_field_text(self.parent_id, session_id);
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(self.doc_type_query(DOC_TYPE_SESSION)) as Box<dyn Query>),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(parent_term, IndexRecordOption::Basic)),
+            ),
+        ]);
+
+        let mut children = Vec::new();
+        for doc_addr in searcher.search(&query, &DocSetCollector)? {
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            children.push(self.session_from_doc(&doc));
+        }
+        children.sort_by_key(|s| s.start_timestamp);
+        Ok(children)
+    }
+
+    /// Get every session in the thread containing the given session
+    /// (its root and all continuations/forks, but not subagents), oldest first
+    pub fn thread(&self, session_id: &str) -> Result<Vec<Session>> {
+        let searcher = self.reader.searcher();
+        let Some(mut root) = self.load_session(&searcher, session_id)? else {
+            return Ok(Vec::new());
+        };
+
+        // A subagent isn't part of its parent's thread
+        if root.is_subagent {
+            return Ok(vec![root]);
+        }
+
+        // Walk up to the root (guarding against cycles)
+        let mut seen = std::collections::HashSet::from([root.id.clone()]);
+        while let Some(parent_id) = root.parent_id.clone() {
+            if !seen.insert(parent_id.clone()) {
+                break;
+            }
+            match self.load_session(&searcher, &parent_id)? {
+                Some(parent) => root = parent,
+                None => break,
+            }
+        }
+
+        // Then collect all descendants
+        let mut seen = std::collections::HashSet::from([root.id.clone()]);
+        let mut thread = vec![root];
+        let mut next = 0;
+        while next < thread.len() {
+            for child in self.children(&thread[next].id)? {
+                if !child.is_subagent && seen.insert(child.id.clone()) {
+                    thread.push(child);
+                }
+            }
+            next += 1;
+        }
+
+        thread.sort_by_key(|s| s.start_timestamp);
+        Ok(thread)
+    }
+
+    /// Get the IDs of the sessions in the thread of each given session (as `thread`
+    /// does for one), with one query per generation of links rather than per session
+    pub fn threads(
+        &self,
+        session_ids: &[&str],
+    ) -> Result<std::collections::HashMap<String, Vec<String>>> {
+        let searcher = self.reader.searcher();
+
+        // Each session reached, with its parent and whether it's a subagent
+        let mut links: std::collections::HashMap<String, (Option<String>, bool)> =
+            std::collections::HashMap::new();
+        let mut frontier: Vec<String> = session_ids.iter().map(|id| id.to_string()).collect();
+        let mut queried = std::collections::HashSet::new();
+        while !frontier.is_empty() {
+            queried.extend(frontier.iter().cloned());
+            // The sessions themselves and their children
+            let ids = |field| frontier.iter().map(move |id| tantivy::Term::from_field_text(field, id));
+            let query = BooleanQuery::new(vec![
+                (Occur::Must, Box::new(self.doc_type_query(DOC_TYPE_SESSION)) as Box<dyn Query>),
+                (
+                    Occur::Must,
+                    Box::new(BooleanQuery::new(vec![
+                        (Occur::Should, Box::new(TermSetQuery::new(ids(self.session_id))) as Box<dyn Query>),
+                        (Occur::Should, Box::new(TermSetQuery::new(ids(self.parent_id)))),
+                    ])),
+                ),
+            ]);
+
+            let mut next = Vec::new();
+            for doc_addr in searcher.search(&query, &DocSetCollector)? {
+                let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+                let session = self.session_from_doc(&doc);
+                // Subagents aren't part of their parent's thread, so aren't followed
+                if !session.is_subagent {
+                    next.extend(session.parent_id.clone());
+                    next.push(session.id.clone());
+                }
+                links.insert(session.id, (session.parent_id, session.is_subagent));
+            }
+            next.retain(|id| !queried.contains(id));
+            next.sort();
+            next.dedup();
+            frontier = next;
+        }
+
+        // Threads link sessions that aren't subagents to their parents, both ways
+        let mut linked: std::collections::HashMap<&str, Vec<&str>> =
+            std::collections::HashMap::new();
+        for (id, (parent_id, is_subagent)) in &links {
+            if let (Some(parent_id), false) = (parent_id, is_subagent) {
+                if links.contains_key(parent_id) {
+                    linked.entry(id).or_default().push(parent_id);
+                    linked.entry(parent_id).or_default().push(id);
+                }
+            }
+        }
+
+        let mut threads = std::collections::HashMap::new();
+        for &id in session_ids {
+            let Some((_, is_subagent)) = links.get(id) else {
+                continue;
+            };
+            let mut thread = vec![id];
+            if !is_subagent {
+                let mut next = 0;
+                while next < thread.len() {
+                    for &other in linked.get(thread[next]).into_iter().flatten() {
+                        if !thread.contains(&other) {
+                            thread.push(other);
+                        }
+                    }
+                    next += 1;
+                }
+            }
+            threads.insert(id.to_string(), thread.into_iter().map(String::from).collect());
+        }
+        Ok(threads)
+    }
+
+    /// Load session metadata from its summary document
+    fn load_session(
+        &self,
+        searcher: &tantivy::Searcher,
+        session_id: &str,
+    ) -> Result<Option<Session>> {
+        let id_term = tantivy::Term::from_field_text(self.session_id, session_id);
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(self.doc_type_query(DOC_TYPE_SESSION)) as Box<dyn Query>),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(id_term, IndexRecordOption::Basic)),
+            ),
+        ]);
 
         let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        match top_docs.first() {
+            Some((_score, doc_addr)) => {
+                let doc: tantivy::TantivyDocument = searcher.doc(*doc_addr)?;
+                Ok(Some(self.session_from_doc(&doc)))
+            }
+            None => Ok(None),
+        }
+    }
 
-        if let Some((_score, doc_addr)) = top_docs.first() {
-            let doc: tantivy::TantivyDocument = searcher.doc(*doc_addr)?;
+    /// Query matching every document of one kind
+    fn doc_type_query(&self, doc_type: &str) -> TermQuery {
+        let term = tantivy::Term::from_field_text(self.doc_type, doc_type);
+        TermQuery::new(term, IndexRecordOption::Basic)
+    }
 
-            let file_path = doc
-                .get_first(self.file_path)
-                .and_then(|v| v.as_str())
-                .map(PathBuf::from);
+    /// Get the session ID of any stored document
+    fn session_id_from_doc(&self, doc: &tantivy::TantivyDocument) -> String {
+        doc.get_first(self.session_id)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    }
 
-            Ok(file_path)
-        } else {
-            Ok(None)
+    /// Build session metadata from a stored summary document (without messages)
+    fn session_from_doc(&self, doc: &tantivy::TantivyDocument) -> Session {
+        let session_id = self.session_id_from_doc(doc);
+
+        let source_str = doc
+            .get_first(self.source)
+            .and_then(|v| v.as_str())
+            .unwrap_or("claude");
+
+        let source = SessionSource::parse(source_str).unwrap_or(SessionSource::ClaudeCode);
+
+        let file_path = doc
+            .get_first(self.file_path)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let cwd = doc
+            .get_first(self.cwd)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let title = doc
+            .get_first(self.title)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let parent_id = doc
+            .get_first(self.parent_id)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty());
+
+        let is_subagent = doc
+            .get_first(self.is_subagent)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let git_branch = doc
+            .get_first(self.git_branch)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty());
+
+        let timestamp_secs = doc
+            .get_first(self.timestamp)
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0);
+
+        let start_secs = doc
+            .get_first(self.start_timestamp)
+            .and_then(|v| v.as_i64())
+            .unwrap_or(timestamp_secs);
+
+        let active_secs = doc
+            .get_first(self.active_secs)
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0);
+
+        Session {
+            id: session_id,
+            source,
+            file_path: PathBuf::from(&file_path),
+            cwd,
+            git_branch,
+            title,
+            parent_id,
+            is_subagent,
+            timestamp: chrono::DateTime::from_timestamp(timestamp_secs, 0).unwrap_or_default(),
+            start_timestamp: chrono::DateTime::from_timestamp(start_secs, 0).unwrap_or_default(),
+            active_duration: chrono::Duration::seconds(active_secs),
+            messages: Vec::new(), // We don't load all messages for search results
         }
     }
 }
-
@@ -1,3 +1,4 @@
+use super::encrypted::IndexKey;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -9,6 +10,9 @@ use std::time::SystemTime;
 pub struct IndexState {
     pub indexed_files: HashMap<PathBuf, FileState>,
     pub version: u32,
+    /// Fingerprint of the ignore rules the files were indexed under
+    #[serde(default)]
+    pub ignore_fingerprint: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,31 +22,39 @@ pub struct FileState {
 }
 
 impl IndexState {
-    const CURRENT_VERSION: u32 = 1;
+    /// Bumped whenever the index schema changes, so every file gets reindexed
+    const CURRENT_VERSION: u32 = 12;
 
-    /// Load state from disk or create new
-    pub fn load(state_path: &Path) -> Result<Self> {
+    /// Load state from disk (decrypting it with the index key, if any) or create new
+    pub fn load(state_path: &Path, key: Option<&IndexKey>) -> Result<Self> {
         if state_path.exists() {
-            let content = std::fs::read_to_string(state_path)
-                .context("Failed to read state file")?;
-            let state: Self = serde_json::from_str(&content)
+            let mut content = std::fs::read(state_path).context("Failed to read state file")?;
+            if let Some(key) = key {
+                content = key.open(&content).context("Failed to decrypt state file")?;
+            }
+            let state: Self = serde_json::from_slice(&content)
                 .context("Failed to parse state file")?;
-            Ok(state)
-        } else {
-            Ok(Self {
-                indexed_files: HashMap::new(),
-                version: Self::CURRENT_VERSION,
-            })
+            if state.version == Self::CURRENT_VERSION {
+                return Ok(state);
+            }
         }
+        Ok(Self {
+            indexed_files: HashMap::new(),
+            version: Self::CURRENT_VERSION,
+            ignore_fingerprint: 0,
+        })
     }
 
-    /// Save state to disk
-    pub fn save(&self, state_path: &Path) -> Result<()> {
+    /// Save state to disk (encrypted with the index key, if any)
+    pub fn save(&self, state_path: &Path, key: Option<&IndexKey>) -> Result<()> {
         if let Some(parent) = state_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let content = serde_json::to_string_pretty(self)
+        let mut content = serde_json::to_vec_pretty(self)
             .context("Failed to serialize state")?;
+        if let Some(key) = key {
+            content = key.seal(&content)?;
+        }
         std::fs::write(state_path, content)
             .context("Failed to write state file")?;
         Ok(())
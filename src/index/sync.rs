@@ -1,6 +1,8 @@
 //! Synchronous indexing for CLI mode
 
-use super::indexer::{discover_and_sort_files, index_files, IndexProgress};
+use super::indexer::{
+    discover_and_sort_files, index_files, purge_if_ignore_rules_changed, IndexProgress,
+};
 use super::schema::default_index_path;
 use super::state::IndexState;
 use super::SessionIndex;
@@ -18,10 +20,11 @@ pub fn ensure_index_fresh(index: &SessionIndex) -> Result<()> {
         .map(|p| p.join("state.json"))
         .unwrap_or_else(|| index_path.join("state.json"));
 
-    let mut state = IndexState::load(&state_path)?;
+    let mut state = IndexState::load(&state_path, index.key())?;
+    let purged = purge_if_ignore_rules_changed(index, &mut state)?;
 
     // Discover all session files
-    let files = discover_and_sort_files();
+    let files = discover_and_sort_files(index.ignore_rules());
 
     // Find files that need indexing
     let files_to_index: Vec<_> = files
@@ -33,6 +36,10 @@ pub fn ensure_index_fresh(index: &SessionIndex) -> Result<()> {
     let total = files_to_index.len();
     if total == 0 {
         // Nothing to index, we're fresh
+        if purged {
+            state.save(&state_path, index.key())?;
+            index.reload()?;
+        }
         return Ok(());
     }
 
@@ -59,7 +66,7 @@ pub fn ensure_index_fresh(index: &SessionIndex) -> Result<()> {
         None, // No reload callback for sync mode
     )?;
 
-    state.save(&state_path)?;
+    state.save(&state_path, index.key())?;
 
     // Clear progress line and print completion
     eprintln!(
@@ -0,0 +1,141 @@
+//! Resuming a session somewhere other than the current terminal: a new tmux window
+//! or split, a zellij pane, or a detached screen session
+
+use crate::archive;
+use crate::config::{ResumeConfig, ResumeTarget};
+use crate::session::Session;
+use anyhow::{Context, Result};
+
+/// The command that opens a session's resume command in `target`, in the session's
+/// directory. With `keep_focus`, the current pane stays focused where the target allows.
+pub fn launch_command(
+    session: &Session,
+    target: ResumeTarget,
+    keep_focus: bool,
+    config: &ResumeConfig,
+) -> Result<(String, Vec<String>)> {
+    let (program, resume_args) = session.resume_command(config)?;
+    let mut args: Vec<String> = Vec::new();
+    let cwd = (!session.cwd.is_empty()).then_some(session.cwd.as_str());
+
+    let program_name = match target {
+        ResumeTarget::TmuxWindow | ResumeTarget::TmuxSplit => {
+            if target == ResumeTarget::TmuxWindow {
+                args.push("new-window".into());
+            } else {
+                args.extend(["split-window".into(), "-h".into()]);
+            }
+            if keep_focus {
+                args.push("-d".into());
+            }
+            if let Some(cwd) = cwd {
+                args.extend(["-c".into(), cwd.to_string()]);
+            }
+            args.push("--".into());
+            "tmux"
+        }
+        ResumeTarget::Zellij => {
+            args.push("run".into());
+            if let Some(cwd) = cwd {
+                args.extend(["--cwd".into(), cwd.to_string()]);
+            }
+            args.extend(["--name".into(), session.title.clone(), "--".into()]);
+            "zellij"
+        }
+        ResumeTarget::Screen => {
+            // screen has no directory option: the process is started in the session's directory
+            args.extend(["-dmS".into(), screen_name(session)]);
+            "screen"
+        }
+    };
+
+    args.push(program);
+    args.extend(resume_args);
+    Ok((program_name.to_string(), args))
+}
+
+/// Name of the screen session a session is resumed in (for `screen -r <name>`)
+pub fn screen_name(session: &Session) -> String {
+    let id: String = session.id.chars().take(8).collect();
+    format!("recall-{}", id)
+}
+
+/// Open a session in `target` and return once the multiplexer has taken over,
+/// leaving the current terminal as it was
+pub fn launch(
+    session: &Session,
+    target: ResumeTarget,
+    keep_focus: bool,
+    config: &ResumeConfig,
+) -> Result<()> {
+    archive::ensure_resumable(session)?;
+    if !target.is_available() {
+        let multiplexer = if target == ResumeTarget::Zellij { "zellij" } else { "tmux" };
+        anyhow::bail!("Not running inside {} (can't open {})", multiplexer, target.display_name());
+    }
+
+    let (program, args) = launch_command(session, target, keep_focus, config)?;
+    let mut command = std::process::Command::new(&program);
+    command
+        .args(&args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped());
+    if !session.cwd.is_empty() && std::path::Path::new(&session.cwd).is_dir() {
+        command.current_dir(&session.cwd);
+    }
+
+    let output = command
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionSource;
+
+    fn session() -> Session {
+        Session::test("3f2a9c1e-7b4d")
+            .with_source(SessionSource::CodexCli)
+            .with_file_path("/tmp/rollout.jsonl")
+            .with_cwd("/work/api")
+            .with_title("Fix the flaky test")
+    }
+
+    #[test]
+    fn test_launch_command_wraps_resume_command() {
+        let session = session();
+        let config = ResumeConfig::default();
+        let (program, resume_args) = session.resume_command(&config).unwrap();
+        let resume: Vec<String> = std::iter::once(program).chain(resume_args).collect();
+
+        let (program, args) =
+            launch_command(&session, ResumeTarget::TmuxSplit, true, &config).unwrap();
+        assert_eq!(program, "tmux");
+        assert_eq!(args[..6], ["split-window", "-h", "-d", "-c", "/work/api", "--"]);
+        assert_eq!(args[6..], resume[..]);
+
+        let (_, args) = launch_command(&session, ResumeTarget::TmuxWindow, false, &config).unwrap();
+        assert_eq!(args[..4], ["new-window", "-c", "/work/api", "--"]);
+
+        let (program, args) =
+            launch_command(&session, ResumeTarget::Zellij, true, &config).unwrap();
+        assert_eq!(program, "zellij");
+        assert_eq!(args[..6], ["run", "--cwd", "/work/api", "--name", "Fix the flaky test", "--"]);
+
+        let (program, args) =
+            launch_command(&session, ResumeTarget::Screen, false, &config).unwrap();
+        assert_eq!(program, "screen");
+        assert_eq!(args[..2], ["-dmS", "recall-3f2a9c1e"]);
+        assert_eq!(args[2..], resume[..]);
+    }
+}
@@ -1,13 +1,26 @@
 pub mod app;
+pub mod archive;
+pub mod config;
+pub mod export;
+pub mod git;
+pub mod handoff;
+pub mod ignore;
 pub mod index;
+pub mod launch;
+pub mod markdown;
 pub mod parser;
+pub mod paths;
+pub mod redact;
+pub mod remap;
 pub mod session;
+pub mod shell;
 pub mod theme;
 pub mod tui;
 pub mod ui;
 
 pub use app::{App, SearchScope};
 pub use session::{
-    ListOutput, Message, ReadOutput, Role, SearchOutput, SearchResult, SearchResultOutput,
-    Session, SessionSource, SessionSummary,
+    BlameOutput, ContentBlock, HandoffOutput, ListOutput, Message, MessageKind, ReadOutput, RelatedOutput, RelatedResultOutput,
+    Role, ScoreBreakdown, SearchOutput, SearchResult, SearchResultOutput, Session, SessionSource,
+    SessionSummary, ThreadMessage, ThreadOutput,
 };
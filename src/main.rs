@@ -1,7 +1,20 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind};
-use recall::{app::App, session, session::SessionSource, tui, ui};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind};
+use recall::{
+    app::{App, ScopeKind, SearchScope},
+    archive,
+    config::{Config, ResumeConfig, ResumeTarget},
+    export::ExportFormat,
+    index::{self, BranchFilter, IndexKey, SortOrder, SubagentFilter},
+    ignore,
+    launch,
+    paths,
+    remap::{self, Remaps},
+    session,
+    session::SessionSource,
+    shell, tui, ui,
+};
 use std::time::Duration;
 
 mod cli;
@@ -17,6 +30,10 @@ struct Cli {
     #[arg(long, global = true)]
     reindex: bool,
 
+    /// Hide projects flagged private in the ignore rules
+    #[arg(long, global = true)]
+    private: bool,
+
     /// Initial search query (for interactive TUI mode)
     #[arg(trailing_var_arg = true)]
     query: Vec<String>,
@@ -54,9 +71,62 @@ enum Command {
         #[arg(long)]
         until: Option<String>,
 
+        /// Apply --since/--until to when sessions started, not their last activity
+        #[arg(long)]
+        by_start: bool,
+
         /// Filter by working directory (exact match)
         #[arg(long)]
         cwd: Option<String>,
+
+        /// Filter by working directory and its subdirectories
+        #[arg(long, conflicts_with_all = ["cwd", "scope"])]
+        cwd_prefix: Option<String>,
+
+        /// Filter to sessions around --cwd or the current directory: folder, subtree,
+        /// repo (its git repository), worktrees (all worktrees and clones of it), everything
+        #[arg(long)]
+        scope: Option<String>,
+
+        /// Filter by git branch (`feature/*` for a prefix)
+        #[arg(long)]
+        branch: Option<String>,
+
+        /// Subagent transcripts: include, exclude, or only
+        #[arg(long, default_value = "include")]
+        subagents: String,
+
+        /// Skip this many matching results
+        #[arg(long, default_value = "0")]
+        offset: usize,
+
+        /// Continue from the `next_page_token` of a previous call
+        #[arg(long, conflicts_with = "offset")]
+        page_token: Option<String>,
+
+        /// Result order (relevance, recent, oldest)
+        #[arg(long, default_value = "relevance")]
+        sort: String,
+
+        /// Include each result's score components in the output
+        #[arg(long)]
+        explain: bool,
+
+        /// Recency boost half-life in days (overrides config)
+        #[arg(long, value_name = "DAYS")]
+        recency_half_life: Option<f64>,
+
+        /// Maximum recency boost, 0 disables it (overrides config)
+        #[arg(long, value_name = "WEIGHT")]
+        recency_weight: Option<f64>,
+
+        /// Boost for exact phrase matches (overrides config)
+        #[arg(long, value_name = "BOOST")]
+        phrase_boost: Option<f32>,
+
+        /// Bonus per message position, favoring later matches (overrides config)
+        #[arg(long, value_name = "WEIGHT")]
+        position_weight: Option<f32>,
     },
 
     /// List recent sessions and output JSON
@@ -77,15 +147,190 @@ enum Command {
         #[arg(long)]
         until: Option<String>,
 
+        /// Apply --since/--until to when sessions started, not their last activity
+        #[arg(long)]
+        by_start: bool,
+
         /// Filter by working directory (exact match)
         #[arg(long)]
         cwd: Option<String>,
+
+        /// Filter by working directory and its subdirectories
+        #[arg(long, conflicts_with_all = ["cwd", "scope"])]
+        cwd_prefix: Option<String>,
+
+        /// Filter to sessions around --cwd or the current directory: folder, subtree,
+        /// repo (its git repository), worktrees (all worktrees and clones of it), everything
+        #[arg(long)]
+        scope: Option<String>,
+
+        /// Filter by git branch (`feature/*` for a prefix)
+        #[arg(long)]
+        branch: Option<String>,
+
+        /// Subagent transcripts: include, exclude, or only
+        #[arg(long, default_value = "include")]
+        subagents: String,
+
+        /// Skip this many matching results
+        #[arg(long, default_value = "0")]
+        offset: usize,
+
+        /// Continue from the `next_page_token` of a previous call
+        #[arg(long, conflicts_with = "offset")]
+        page_token: Option<String>,
     },
 
     /// Read a full conversation by session ID and output JSON
     Read {
         /// Session ID to read
         session_id: String,
+
+        /// Include every continuation and fork of the session, stitched together
+        #[arg(long)]
+        thread: bool,
+
+        /// Include the agent's thinking/reasoning with its messages
+        #[arg(long)]
+        include_reasoning: bool,
+
+        /// Include tool calls and their results (as content blocks) with the messages
+        #[arg(long)]
+        include_tools: bool,
+
+        /// Include the commits made during the session (always on with `[git] commits`)
+        #[arg(long)]
+        commits: bool,
+    },
+
+    /// Export a conversation as a readable transcript, or all of them as a bundle
+    Export {
+        /// Session ID to export
+        #[arg(required_unless_present = "all")]
+        session_id: Option<String>,
+
+        /// Export every session matching the filters into a directory (or a .tar
+        /// file; zip isn't supported) of transcripts with a manifest
+        #[arg(long, conflicts_with = "session_id", requires = "output")]
+        all: bool,
+
+        /// Output format: md, html, txt, or json (default: from the -o extension, else
+        /// md; json for --all)
+        #[arg(long, short)]
+        format: Option<String>,
+
+        /// Write to this file (or bundle directory) instead of stdout
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+
+        /// Include the agent's thinking/reasoning with its messages
+        #[arg(long)]
+        include_reasoning: bool,
+
+        /// With --all: filter by source (claude, codex, factory, opencode)
+        #[arg(long, short, requires = "all")]
+        source: Option<String>,
+
+        /// With --all: only include sessions after this time
+        #[arg(long, requires = "all")]
+        since: Option<String>,
+
+        /// With --all: only include sessions before this time
+        #[arg(long, requires = "all")]
+        until: Option<String>,
+
+        /// Apply --since/--until to when sessions started, not their last activity
+        #[arg(long, requires = "all")]
+        by_start: bool,
+
+        /// Filter by working directory (exact match)
+        #[arg(long, requires = "all")]
+        cwd: Option<String>,
+
+        /// Filter by working directory and its subdirectories
+        #[arg(long, requires = "all", conflicts_with_all = ["cwd", "scope"])]
+        cwd_prefix: Option<String>,
+
+        /// Filter to sessions around --cwd or the current directory: folder, subtree,
+        /// repo (its git repository), worktrees (all worktrees and clones of it), everything
+        #[arg(long, requires = "all")]
+        scope: Option<String>,
+
+        /// Filter by git branch (`feature/*` for a prefix)
+        #[arg(long, requires = "all")]
+        branch: Option<String>,
+
+        /// Subagent transcripts: include, exclude, or only
+        #[arg(long, requires = "all", default_value = "include")]
+        subagents: String,
+    },
+
+    /// Import a JSON bundle from `export --all` as read-only archived sessions
+    Import {
+        /// Bundle directory or .tar file (zip isn't supported)
+        bundle: std::path::PathBuf,
+    },
+
+    /// Continue a conversation in another agent: write it as a new session in that
+    /// agent's format and output JSON with the command to resume it
+    Handoff {
+        /// Session ID to hand off
+        session_id: String,
+
+        /// Agent to hand off to (claude, codex, opencode)
+        #[arg(long)]
+        to: String,
+    },
+
+    /// Resume a conversation by session ID (or a unique prefix of one)
+    Resume {
+        /// Session ID or prefix
+        session_id: String,
+
+        /// Open it in a new tmux-window, tmux-split, zellij pane or detached screen
+        /// session instead of the current terminal
+        #[arg(long = "in", value_name = "TARGET")]
+        target: Option<String>,
+
+        /// Output JSON with the command instead of running it
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Resume the most recent conversation in the current directory
+    Last {
+        /// Only consider this source (claude, codex, factory, opencode)
+        #[arg(long, short)]
+        source: Option<String>,
+
+        /// Open it in a new tmux-window, tmux-split, zellij pane or detached screen
+        /// session instead of the current terminal
+        #[arg(long = "in", value_name = "TARGET")]
+        target: Option<String>,
+
+        /// Output JSON with the command instead of running it
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Find the conversation a git commit was made in and output JSON
+    Blame {
+        /// Commit hash (or any revision, e.g. HEAD~2)
+        commit: String,
+
+        /// Repository to look the commit up in (default: the current directory)
+        #[arg(long)]
+        cwd: Option<String>,
+    },
+
+    /// Find sessions with similar content to a session and output JSON
+    Related {
+        /// Session ID to find related sessions for
+        session_id: String,
+
+        /// Maximum number of results
+        #[arg(long, short, default_value = "10")]
+        limit: usize,
     },
 }
 
@@ -107,9 +352,43 @@ fn main() -> Result<()> {
             context,
             since,
             until,
+            by_start,
             cwd,
+            cwd_prefix,
+            scope,
+            branch,
+            subagents,
+            offset,
+            page_token,
+            sort,
+            explain,
+            recency_half_life,
+            recency_weight,
+            phrase_boost,
+            position_weight,
         }) => {
             let source = parse_source(&source)?;
+            let subagents = parse_subagents(&subagents)?;
+            let scope = parse_scope(cwd, cwd_prefix, scope)?;
+            let sort = SortOrder::parse(&sort).ok_or_else(|| {
+                anyhow::anyhow!("Invalid sort '{}'. Valid: relevance, recent, oldest", sort)
+            })?;
+
+            // Command-line weights override the config file
+            let mut ranking = Config::load()?.ranking;
+            if let Some(v) = recency_half_life {
+                ranking.recency_half_life_days = v;
+            }
+            if let Some(v) = recency_weight {
+                ranking.recency_weight = v;
+            }
+            if let Some(v) = phrase_boost {
+                ranking.phrase_boost = v;
+            }
+            if let Some(v) = position_weight {
+                ranking.message_position_weight = v;
+            }
+
             cli::run_search(
                 &query.join(" "),
                 source,
@@ -118,7 +397,16 @@ fn main() -> Result<()> {
                 context,
                 since,
                 until,
-                cwd,
+                by_start,
+                scope,
+                branch.as_deref().map(BranchFilter::new),
+                subagents,
+                offset,
+                page_token,
+                sort,
+                ranking,
+                explain,
+                cli.private,
             )
         }
         Some(Command::List {
@@ -126,16 +414,119 @@ fn main() -> Result<()> {
             source,
             since,
             until,
+            by_start,
             cwd,
+            cwd_prefix,
+            scope,
+            branch,
+            subagents,
+            offset,
+            page_token,
         }) => {
             let source = parse_source(&source)?;
-            cli::run_list(limit, source, since, until, cwd)
+            let subagents = parse_subagents(&subagents)?;
+            let scope = parse_scope(cwd, cwd_prefix, scope)?;
+            cli::run_list(
+                limit,
+                source,
+                since,
+                until,
+                by_start,
+                scope,
+                branch.as_deref().map(BranchFilter::new),
+                subagents,
+                offset,
+                page_token,
+                cli.private,
+            )
+        }
+        Some(Command::Read {
+            session_id,
+            thread,
+            include_reasoning,
+            include_tools,
+            commits,
+        }) => {
+            let commits = commits || Config::load()?.git.commits;
+            cli::run_read(&session_id, thread, include_reasoning, include_tools, commits)
+        }
+        Some(Command::Export {
+            session_id,
+            all,
+            format,
+            output,
+            include_reasoning,
+            source,
+            since,
+            until,
+            by_start,
+            cwd,
+            cwd_prefix,
+            scope,
+            branch,
+            subagents,
+        }) => match (session_id, output) {
+            (Some(session_id), output) => {
+                let format = parse_export_format(format.as_deref(), output.as_deref())?;
+                cli::run_export(&session_id, format, output.as_deref(), include_reasoning)
+            }
+            (None, Some(output)) if all => {
+                let format = match format {
+                    Some(_) => parse_export_format(format.as_deref(), None)?,
+                    None => ExportFormat::Json,
+                };
+                cli::run_export_all(
+                    format,
+                    &output,
+                    include_reasoning,
+                    parse_source(&source)?,
+                    since,
+                    until,
+                    by_start,
+                    parse_scope(cwd, cwd_prefix, scope)?,
+                    branch.as_deref().map(BranchFilter::new),
+                    parse_subagents(&subagents)?,
+                    cli.private,
+                )
+            }
+            _ => anyhow::bail!("Give a session ID, or --all with -o <dir or .tar file>"),
+        },
+        Some(Command::Import { bundle }) => cli::run_import(&bundle),
+        Some(Command::Resume { session_id, target, print }) => {
+            let session = cli::resolve_session(&session_id)?;
+            let target = parse_resume_target(&target)?;
+            if print {
+                return print_resume_command(&session, target);
+            }
+            resume_session_in(&session, target)
+        }
+        Some(Command::Last { source, target, print }) => {
+            let session = cli::last_session(parse_source(&source)?, cli.private)?;
+            let target = parse_resume_target(&target)?;
+            if print {
+                return print_resume_command(&session, target);
+            }
+            eprintln!("Resuming {} ({})", session.title, session.id);
+            resume_session_in(&session, target)
+        }
+        Some(Command::Handoff { session_id, to }) => {
+            let target = parse_source(&Some(to))?.expect("source is given");
+            cli::run_handoff(&session_id, target)
+        }
+        Some(Command::Blame { commit, cwd }) => {
+            let dir = match cwd {
+                Some(cwd) => std::path::PathBuf::from(ignore::expand_home(&cwd)),
+                None => std::env::current_dir()?,
+            };
+            cli::run_blame(&commit, &dir, cli.private)
+        }
+        Some(Command::Related { session_id, limit }) => {
+            cli::run_related(&session_id, limit, cli.private)
         }
-        Some(Command::Read { session_id }) => cli::run_read(&session_id),
         None => {
             // Interactive TUI mode
             let initial_query = cli.query.join(" ");
-            run_tui(initial_query)
+            run_tui(initial_query, cli.private)
         }
     }
 }
@@ -149,9 +540,70 @@ fn parse_source(source: &Option<String>) -> Result<Option<SessionSource>> {
     }
 }
 
-fn run_tui(initial_query: String) -> Result<()> {
+/// Directory filter from `--cwd`, `--cwd-prefix` and `--scope` (around `--cwd`, or
+/// else the current directory)
+fn parse_scope(
+    cwd: Option<String>,
+    cwd_prefix: Option<String>,
+    scope: Option<String>,
+) -> Result<Option<SearchScope>> {
+    if let Some(prefix) = cwd_prefix {
+        return Ok(Some(SearchScope::Subtree(ignore::expand_home(&prefix))));
+    }
+    let kind = match &scope {
+        Some(s) => ScopeKind::parse(s).ok_or_else(|| {
+            anyhow::anyhow!("Invalid scope '{}'. Valid: folder, subtree, repo, worktrees, everything", s)
+        })?,
+        None if cwd.is_some() => ScopeKind::Folder,
+        None => return Ok(None),
+    };
+    let dir = match cwd {
+        Some(cwd) => cwd,
+        None => std::env::current_dir()?.to_string_lossy().to_string(),
+    };
+    match SearchScope::around(kind, &dir, || cli::known_cwds().unwrap_or_default()) {
+        Some(SearchScope::Everything) => Ok(None),
+        Some(scope) => Ok(Some(scope)),
+        None => anyhow::bail!("{} isn't in a git repository", dir),
+    }
+}
+
+fn parse_resume_target(target: &Option<String>) -> Result<Option<ResumeTarget>> {
+    match target {
+        Some(t) => ResumeTarget::parse(t)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Invalid target '{}'. Valid: tmux-window, tmux-split, zellij, screen", t)
+            })
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Export format from `--format`, or else the output file's extension (markdown by default)
+fn parse_export_format(format: Option<&str>, output: Option<&std::path::Path>) -> Result<ExportFormat> {
+    match format {
+        Some(f) => ExportFormat::parse(f)
+            .ok_or_else(|| anyhow::anyhow!("Invalid export format '{}'. Valid: md, html, txt, json", f)),
+        None => Ok(output
+            .and_then(|p| p.extension())
+            .and_then(|ext| ExportFormat::parse(&ext.to_string_lossy().to_lowercase()))
+            .unwrap_or(ExportFormat::Markdown)),
+    }
+}
+
+fn parse_subagents(subagents: &str) -> Result<SubagentFilter> {
+    SubagentFilter::parse(subagents).ok_or_else(|| {
+        anyhow::anyhow!("Invalid subagents filter '{}'. Valid: include, exclude, only", subagents)
+    })
+}
+
+fn run_tui(initial_query: String, private: bool) -> Result<()> {
     // Initialize app (starts background indexing automatically)
-    let mut app = App::new(initial_query)?;
+    let key = unlock_index()?;
+    let mut app = App::with_key(initial_query, key)?;
+    if private {
+        app.set_private(true);
+    }
 
     // Initialize terminal
     let mut terminal = tui::init()?;
@@ -170,7 +622,7 @@ fn run_tui(initial_query: String) -> Result<()> {
 
     // Handle post-exit actions
     if let Some(session) = app.should_resume {
-        resume_session(&session)?;
+        resume_session_in(&session, None)?;
     } else if let Some(session_id) = app.should_copy {
         copy_to_clipboard(&session_id)?;
         println!("Copied session ID: {}", session_id);
@@ -204,10 +656,33 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
             match event::read()? {
                 // On Windows, crossterm sends both Press and Release events.
                 // Only handle Press to avoid double input.
+                Event::Key(key) if key.kind == KeyEventKind::Press && on_target_picker_key(app, key) => {}
+                Event::Key(key) if key.kind == KeyEventKind::Press && on_find_key(app, key) => {}
                 Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
                     KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         app.should_quit = true;
                     }
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.show_related();
+                    }
+                    KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.toggle_thread_grouping();
+                    }
+                    KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.cycle_subagent_filter();
+                    }
+                    KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.step_thread(false);
+                    }
+                    KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.step_thread(true);
+                    }
+                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.open_find();
+                    }
+                    KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.open_target_picker();
+                    }
                     KeyCode::Esc => app.on_escape(),
                     KeyCode::Enter => app.on_enter(),
                     KeyCode::Tab => app.on_tab(),
@@ -222,7 +697,7 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
                     KeyCode::PageDown => app.focus_next_message(),
                     KeyCode::Backspace => app.on_backspace(),
                     KeyCode::Char(' ') => app.toggle_focused_expansion(),
-                    KeyCode::Char('/') => app.toggle_scope(),
+                    KeyCode::Char('/') => app.cycle_scope(),
                     KeyCode::Char(c) => app.on_char(c),
                     _ => {}
                 },
@@ -263,17 +738,62 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
     Ok(())
 }
 
+/// Handle a key while choosing where to open a session. Returns false when the
+/// picker isn't open; while it is, other keys are ignored.
+fn on_target_picker_key(app: &mut App, key: KeyEvent) -> bool {
+    if app.target_picker.is_none() {
+        return false;
+    }
+    match key.code {
+        KeyCode::Esc => app.close_target_picker(),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.should_quit = true;
+        }
+        KeyCode::Enter => app.confirm_target(None),
+        KeyCode::Char(c) if c.is_ascii_digit() => {
+            app.confirm_target(c.to_digit(10).map(|n| n as usize));
+        }
+        KeyCode::Left | KeyCode::Up | KeyCode::BackTab => app.step_target(false),
+        KeyCode::Right | KeyCode::Down | KeyCode::Tab => app.step_target(true),
+        _ => {}
+    }
+    true
+}
+
+/// Handle a key while finding in the preview. Returns false for the keys that
+/// act as usual (which, for typing and moving to another session, ends find).
+fn on_find_key(app: &mut App, key: KeyEvent) -> bool {
+    let Some(editing) = app.find.as_ref().map(|f| f.editing) else {
+        return false;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => app.close_find(),
+        KeyCode::Char('f') if ctrl => app.open_find(),
+        KeyCode::Enter if editing => app.confirm_find(),
+        KeyCode::Backspace if editing => app.on_find_backspace(),
+        KeyCode::Char(c) if editing && !ctrl => app.on_find_char(c),
+        KeyCode::Char('n') if !ctrl => app.step_find(true),
+        KeyCode::Char('N') => app.step_find(false),
+        _ => return false,
+    }
+    true
+}
+
 /// Resume a session by exec'ing into the appropriate CLI
 #[cfg(unix)]
-fn resume_session(session: &session::Session) -> Result<()> {
+fn resume_session(session: &session::Session, config: &ResumeConfig) -> Result<()> {
     use std::os::unix::process::CommandExt;
 
+    archive::ensure_resumable(session)?;
+
     // Change to conversation's working directory
     if !session.cwd.is_empty() {
-        let _ = std::env::set_current_dir(&session.cwd);
+        std::env::set_current_dir(&session.cwd)
+            .map_err(|e| anyhow::anyhow!("Couldn't change to {}: {}", session.cwd, e))?;
     }
 
-    let (program, args) = session.resume_command();
+    let (program, args) = session.resume_command(config)?;
 
     // This replaces the current process - never returns on success
     let err = std::process::Command::new(&program).args(&args).exec();
@@ -283,13 +803,16 @@ fn resume_session(session: &session::Session) -> Result<()> {
 }
 
 #[cfg(not(unix))]
-fn resume_session(session: &session::Session) -> Result<()> {
+fn resume_session(session: &session::Session, config: &ResumeConfig) -> Result<()> {
+    archive::ensure_resumable(session)?;
+
     // Change to conversation's working directory
     if !session.cwd.is_empty() {
-        let _ = std::env::set_current_dir(&session.cwd);
+        std::env::set_current_dir(&session.cwd)
+            .map_err(|e| anyhow::anyhow!("Couldn't change to {}: {}", session.cwd, e))?;
     }
 
-    let (program, args) = session.resume_command();
+    let (program, args) = session.resume_command(config)?;
 
     // On non-Unix, just spawn the process
     std::process::Command::new(&program)
@@ -299,6 +822,104 @@ fn resume_session(session: &session::Session) -> Result<()> {
     Ok(())
 }
 
+/// Resume a session in `target`, or else in the current terminal
+fn resume_session_in(session: &session::Session, target: Option<ResumeTarget>) -> Result<()> {
+    let config = Config::load()?.resume;
+    let mut session = session.clone();
+    if !session.is_archived() {
+        locate_cwd(&mut session)?;
+    }
+    match target {
+        Some(ResumeTarget::Screen) => {
+            launch::launch(&session, ResumeTarget::Screen, false, &config)?;
+            eprintln!("Resumed in screen: screen -r {}", launch::screen_name(&session));
+            Ok(())
+        }
+        Some(target) => launch::launch(&session, target, false, &config),
+        None => resume_session(&session, &config),
+    }
+}
+
+/// Make sure a session's directory exists before resuming it: follow a recorded
+/// remapping if the directory moved, or else ask where it went (and record that)
+fn locate_cwd(session: &mut session::Session) -> Result<()> {
+    use std::io::{IsTerminal, Write};
+
+    let mut remaps = Remaps::load()?;
+    let old = session.cwd.clone();
+    if remap::remap_session_cwd(session, &remaps) {
+        if session.cwd != old {
+            eprintln!("{} moved to {}", old, session.cwd);
+        }
+        return Ok(());
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Directory {} no longer exists. Run `recall resume {}` in a terminal to choose where it moved",
+            old,
+            session.id
+        );
+    }
+
+    let known = cli::known_cwds()?;
+    let candidates = remap::candidates(&old, known.iter().map(|s| s.as_str()));
+    eprintln!("{} no longer exists.", old);
+    if candidates.is_empty() {
+        eprintln!("No known project folder looks like it.");
+    } else {
+        eprintln!("It may have moved to:");
+        for (i, candidate) in candidates.iter().enumerate() {
+            eprintln!("  {}) {}", i + 1, candidate);
+        }
+    }
+    eprint!("Choose a number, or type its new path (empty to cancel): ");
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    if answer.is_empty() {
+        anyhow::bail!("Cancelled");
+    }
+    let new = match answer.parse::<usize>() {
+        Ok(n) if (1..=candidates.len()).contains(&n) => candidates[n - 1].clone(),
+        _ => ignore::expand_home(answer),
+    };
+    let new = std::fs::canonicalize(&new)
+        .ok()
+        .filter(|p| p.is_dir())
+        .ok_or_else(|| anyhow::anyhow!("Not a directory: {}", new))?
+        .to_string_lossy()
+        .to_string();
+
+    remaps.record(&old, &new);
+    remaps.save()?;
+    eprintln!("Sessions from {} will resume in {}", old, new);
+    session.cwd = new;
+    Ok(())
+}
+
+/// Output the exact command `resume_session_in` would run, as JSON
+fn print_resume_command(session: &session::Session, target: Option<ResumeTarget>) -> Result<()> {
+    let config = Config::load()?.resume;
+    let mut session = session.clone();
+    remap::remap_session_cwd(&mut session, &Remaps::load()?);
+    let (program, args) = match target {
+        Some(target) => launch::launch_command(&session, target, false, &config)?,
+        None => session.resume_command(&config)?,
+    };
+    let argv: Vec<String> = std::iter::once(program).chain(args).collect();
+    let output = serde_json::json!({
+        "session_id": session.id,
+        "cwd": session.cwd,
+        "target": target.map(|t| t.as_str()),
+        "command": shell::join(&argv),
+        "argv": argv,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
 /// Copy session ID to clipboard
 fn copy_to_clipboard(text: &str) -> Result<()> {
     use arboard::Clipboard;
@@ -307,15 +928,45 @@ fn copy_to_clipboard(text: &str) -> Result<()> {
     Ok(())
 }
 
+/// Prompt for the passphrase of an encrypted index, unless the environment or a
+/// keyfile provides the key (then the app unlocks it by itself)
+fn unlock_index() -> Result<Option<IndexKey>> {
+    let config = Config::load()?.encryption;
+    if !config.enabled || index::secret_from_env(&config)?.is_some() {
+        return Ok(None);
+    }
+
+    let index_path = index::default_index_path();
+    if !index::is_set_up(&index_path) {
+        let passphrase = tui::prompt_passphrase("New passphrase for the recall index: ")?;
+        if passphrase.is_empty() {
+            anyhow::bail!("The passphrase can't be empty");
+        }
+        if tui::prompt_passphrase("Repeat passphrase: ")? != passphrase {
+            anyhow::bail!("Passphrases don't match");
+        }
+        return IndexKey::unlock(&index_path, passphrase.as_bytes()).map(Some);
+    }
+
+    let mut attempts = 0;
+    loop {
+        let passphrase = tui::prompt_passphrase("Passphrase for the recall index: ")?;
+        match IndexKey::unlock(&index_path, passphrase.as_bytes()) {
+            Ok(key) => return Ok(Some(key)),
+            Err(e) => {
+                attempts += 1;
+                if attempts == 3 {
+                    return Err(e);
+                }
+                eprintln!("{}", e);
+            }
+        }
+    }
+}
+
 /// Clear the index cache directory
 fn clear_index_cache() {
-    let cache_dir = std::env::var("RECALL_HOME_OVERRIDE")
-        .map(|h| std::path::PathBuf::from(h).join(".cache").join("recall"))
-        .unwrap_or_else(|_| {
-            dirs::cache_dir()
-                .unwrap_or_else(|| std::path::PathBuf::from("."))
-                .join("recall")
-        });
+    let cache_dir = paths::cache_dir();
 
     if cache_dir.exists() {
         let _ = std::fs::remove_dir_all(&cache_dir);
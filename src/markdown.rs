@@ -0,0 +1,628 @@
+//! Rendering of the markdown subset agents write (headings, emphasis, lists,
+//! quotes and fenced code) into styled, wrapped lines for the preview pane
+
+/// How a piece of rendered text should look (mapped to theme colors by the UI)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Text,
+    Bold,
+    Italic,
+    InlineCode,
+    Heading,
+    Quote,
+    ListMarker,
+    Rule,
+    /// Language label above a code block
+    CodeLabel,
+    Code,
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub text: String,
+    pub kind: SpanKind,
+}
+
+impl StyledSpan {
+    fn new(text: impl Into<String>, kind: SpanKind) -> Self {
+        Self {
+            text: text.into(),
+            kind,
+        }
+    }
+}
+
+/// A rendered line, no wider than the width it was rendered for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StyledLine {
+    pub spans: Vec<StyledSpan>,
+}
+
+impl StyledLine {
+    /// The line's plain text
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    fn push(&mut self, text: &str, kind: SpanKind) {
+        match self.spans.last_mut() {
+            Some(last) if last.kind == kind => last.text.push_str(text),
+            _ => self.spans.push(StyledSpan::new(text, kind)),
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.spans.iter().map(|s| s.text.chars().count()).sum()
+    }
+}
+
+/// Render markdown text into styled lines wrapped to `width` characters
+pub fn render(text: &str, width: usize) -> Vec<StyledLine> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    // Language of the fenced code block we're in, if any
+    let mut code_lang: Option<String> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if code_lang.take().is_none() {
+                let lang = trimmed[3..].trim().to_lowercase();
+                if !lang.is_empty() {
+                    lines.push(StyledLine {
+                        spans: vec![StyledSpan::new(lang.clone(), SpanKind::CodeLabel)],
+                    });
+                }
+                code_lang = Some(lang);
+            }
+            continue;
+        }
+
+        if let Some(lang) = &code_lang {
+            lines.extend(break_spans(&highlight_code(line, lang), width));
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            lines.push(StyledLine::default());
+            continue;
+        }
+
+        if is_rule(trimmed) {
+            lines.push(StyledLine {
+                spans: vec![StyledSpan::new("─".repeat(width), SpanKind::Rule)],
+            });
+            continue;
+        }
+
+        if let Some(heading) = heading_text(trimmed) {
+            let spans = restyle(parse_inline(heading), SpanKind::Heading);
+            lines.extend(wrap_spans(&spans, &[], &[], width));
+            continue;
+        }
+
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            let spans = restyle(parse_inline(quote.trim_start()), SpanKind::Quote);
+            let prefix = [StyledSpan::new("│ ", SpanKind::Quote)];
+            lines.extend(wrap_spans(&spans, &prefix, &prefix, width));
+            continue;
+        }
+
+        if let Some((indent, marker, item)) = list_item(line) {
+            let mut first = Vec::new();
+            if indent > 0 {
+                first.push(StyledSpan::new(" ".repeat(indent), SpanKind::Text));
+            }
+            first.push(StyledSpan::new(format!("{} ", marker), SpanKind::ListMarker));
+            let rest = [StyledSpan::new(
+                " ".repeat(indent + marker.chars().count() + 1),
+                SpanKind::Text,
+            )];
+            lines.extend(wrap_spans(&parse_inline(item), &first, &rest, width));
+            continue;
+        }
+
+        lines.extend(wrap_spans(&parse_inline(line), &[], &[], width));
+    }
+
+    lines
+}
+
+/// Strip markdown markup from text without wrapping it, so raw text (like a
+/// search snippet) can be found among rendered lines
+pub fn plain_text(text: &str) -> String {
+    text.lines()
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            !(trimmed.starts_with("```") || trimmed.starts_with("~~~"))
+        })
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let content = heading_text(trimmed)
+                .or_else(|| trimmed.strip_prefix('>'))
+                .or_else(|| list_item(line).map(|(_, _, item)| item))
+                .unwrap_or(line);
+            parse_inline(content).into_iter().map(|s| s.text).collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render markdown text as an HTML fragment (for transcripts exported as HTML).
+/// Headings start at `<h3>` so they sit below the transcript's own.
+pub fn to_html(text: &str) -> String {
+    let mut html = String::new();
+    // Block element currently open (`p`, `ul`, `ol` or `blockquote`)
+    let mut open: Option<&str> = None;
+    let mut code_lang: Option<String> = None;
+
+    fn close(html: &mut String, open: &mut Option<&str>) {
+        if let Some(tag) = open.take() {
+            html.push_str(&format!("</{}>\n", tag));
+        }
+    }
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if code_lang.take().is_some() {
+                html.push_str("</code></pre>\n");
+            } else {
+                close(&mut html, &mut open);
+                let lang = trimmed[3..].trim().to_lowercase();
+                if lang.is_empty() {
+                    html.push_str("<pre><code>");
+                } else {
+                    html.push_str(&format!(
+                        "<pre><code class=\"language-{}\">",
+                        escape_html(&lang)
+                    ));
+                }
+                code_lang = Some(lang);
+            }
+            continue;
+        }
+
+        if let Some(lang) = &code_lang {
+            html.push_str(&spans_html(&highlight_code(line, lang)));
+            html.push('\n');
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            close(&mut html, &mut open);
+            continue;
+        }
+
+        if is_rule(trimmed) {
+            close(&mut html, &mut open);
+            html.push_str("<hr>\n");
+            continue;
+        }
+
+        if let Some(heading) = heading_text(trimmed) {
+            close(&mut html, &mut open);
+            let level = (trimmed.chars().take_while(|c| *c == '#').count() + 2).min(6);
+            html.push_str(&format!(
+                "<h{0}>{1}</h{0}>\n",
+                level,
+                spans_html(&parse_inline(heading))
+            ));
+            continue;
+        }
+
+        let (tag, content) = if let Some(quote) = trimmed.strip_prefix('>') {
+            ("blockquote", spans_html(&parse_inline(quote.trim_start())))
+        } else if let Some((_, marker, item)) = list_item(line) {
+            let tag = if marker == "•" { "ul" } else { "ol" };
+            (tag, format!("<li>{}</li>", spans_html(&parse_inline(item))))
+        } else {
+            ("p", spans_html(&parse_inline(trimmed)))
+        };
+        if open != Some(tag) {
+            close(&mut html, &mut open);
+            html.push_str(&format!("<{}>", tag));
+            open = Some(tag);
+        } else if tag == "p" || tag == "blockquote" {
+            html.push('\n');
+        }
+        html.push_str(&content);
+    }
+
+    close(&mut html, &mut open);
+    if code_lang.is_some() {
+        html.push_str("</code></pre>\n");
+    }
+    html
+}
+
+/// Escape text for use in HTML content or attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn spans_html(spans: &[StyledSpan]) -> String {
+    spans
+        .iter()
+        .map(|span| {
+            let text = escape_html(&span.text);
+            match span.kind {
+                SpanKind::Bold => format!("<strong>{}</strong>", text),
+                SpanKind::Italic => format!("<em>{}</em>", text),
+                SpanKind::InlineCode => format!("<code>{}</code>", text),
+                SpanKind::Keyword => format!("<span class=\"kw\">{}</span>", text),
+                SpanKind::String => format!("<span class=\"str\">{}</span>", text),
+                SpanKind::Comment => format!("<span class=\"com\">{}</span>", text),
+                SpanKind::Number => format!("<span class=\"num\">{}</span>", text),
+                _ => text,
+            }
+        })
+        .collect()
+}
+
+/// `---`, `***` or `___` (three or more, optionally spaced)
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3
+        && ['-', '*', '_'].contains(&chars[0])
+        && chars.iter().all(|c| *c == chars[0])
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) {
+        line[level..].strip_prefix(' ').map(|rest| rest.trim())
+    } else {
+        None
+    }
+}
+
+/// Split a list item into its indent, display marker and text
+fn list_item(line: &str) -> Option<(usize, String, &str)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(bullet) {
+            return Some((indent, "•".to_string(), item));
+        }
+    }
+
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &trimmed[digits..];
+        if let Some(item) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some((indent, trimmed[..digits + 1].to_string(), item));
+        }
+    }
+
+    None
+}
+
+/// Parse inline emphasis and code spans, dropping their markers.
+/// Markers without a closing counterpart are kept as text.
+fn parse_inline(text: &str) -> Vec<StyledSpan> {
+    let mut line = StyledLine::default();
+    let mut bold = false;
+    let mut italic = false;
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                line.push(&rest[1..end + 1], SpanKind::InlineCode);
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if rest.starts_with("**") {
+            if bold || rest[2..].contains("**") {
+                bold = !bold;
+                rest = &rest[2..];
+                continue;
+            }
+        } else if c == '*' {
+            let opens = rest[1..].chars().next().is_some_and(|n| !n.is_whitespace())
+                && rest[1..].contains('*');
+            if italic || opens {
+                italic = !italic;
+                rest = &rest[1..];
+                continue;
+            }
+        }
+
+        let kind = if bold {
+            SpanKind::Bold
+        } else if italic {
+            SpanKind::Italic
+        } else {
+            SpanKind::Text
+        };
+        line.push(&rest[..c.len_utf8()], kind);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    line.spans
+}
+
+/// Give plain text spans another style (inline code keeps its own)
+fn restyle(spans: Vec<StyledSpan>, kind: SpanKind) -> Vec<StyledSpan> {
+    spans
+        .into_iter()
+        .map(|mut span| {
+            if span.kind != SpanKind::InlineCode {
+                span.kind = kind;
+            }
+            span
+        })
+        .collect()
+}
+
+/// Word-wrap styled text, starting the first line with `first_prefix` and the
+/// others with `rest_prefix`. Words longer than a line are broken.
+fn wrap_spans(
+    spans: &[StyledSpan],
+    first_prefix: &[StyledSpan],
+    rest_prefix: &[StyledSpan],
+    width: usize,
+) -> Vec<StyledLine> {
+    // Split into words, each possibly made of several styles
+    let mut words: Vec<StyledLine> = Vec::new();
+    let mut word = StyledLine::default();
+    for span in spans {
+        for c in span.text.chars() {
+            if c.is_whitespace() {
+                if !word.spans.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            } else {
+                word.push(&c.to_string(), span.kind);
+            }
+        }
+    }
+    if !word.spans.is_empty() {
+        words.push(word);
+    }
+
+    let new_line = |prefix: &[StyledSpan]| StyledLine {
+        spans: prefix.to_vec(),
+    };
+    let mut lines = Vec::new();
+    let mut line = new_line(first_prefix);
+    let mut line_has_words = false;
+
+    for word in words {
+        let word_width = word.width();
+        if line_has_words && line.width() + 1 + word_width > width {
+            lines.push(std::mem::replace(&mut line, new_line(rest_prefix)));
+            line_has_words = false;
+        }
+        if line_has_words {
+            line.push(" ", SpanKind::Text);
+        }
+
+        for span in word.spans {
+            for c in span.text.chars() {
+                if line.width() >= width {
+                    lines.push(std::mem::replace(&mut line, new_line(rest_prefix)));
+                }
+                line.push(&c.to_string(), span.kind);
+            }
+        }
+        line_has_words = true;
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// Break styled text into lines of `width` characters, keeping all whitespace (code)
+fn break_spans(spans: &[StyledSpan], width: usize) -> Vec<StyledLine> {
+    let mut lines = Vec::new();
+    let mut line = StyledLine::default();
+    let mut line_width = 0;
+
+    for span in spans {
+        for c in span.text.chars() {
+            if line_width >= width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+            let c = if c == '\t' { ' ' } else { c };
+            line.push(&c.to_string(), span.kind);
+            line_width += 1;
+        }
+    }
+
+    lines.push(line);
+    lines
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "self", "True", "try",
+    "while", "with", "yield",
+];
+const JS_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete",
+    "else", "export", "extends", "false", "finally", "for", "from", "function", "if", "import",
+    "in", "instanceof", "interface", "let", "new", "null", "of", "return", "switch", "this",
+    "throw", "true", "try", "type", "typeof", "undefined", "var", "void", "while", "yield",
+];
+const GO_KEYWORDS: &[&str] = &[
+    "break", "case", "chan", "const", "continue", "default", "defer", "else", "false", "for",
+    "func", "go", "if", "import", "interface", "map", "nil", "package", "range", "return",
+    "select", "struct", "switch", "true", "type", "var",
+];
+const SHELL_KEYWORDS: &[&str] = &[
+    "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in",
+    "local", "return", "then", "until", "while",
+];
+
+/// Keywords and line-comment marker of a code block language
+fn language(lang: &str) -> Option<(&'static [&'static str], &'static str)> {
+    match lang {
+        "rust" | "rs" => Some((RUST_KEYWORDS, "//")),
+        "python" | "py" => Some((PYTHON_KEYWORDS, "#")),
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => Some((JS_KEYWORDS, "//")),
+        "go" | "golang" => Some((GO_KEYWORDS, "//")),
+        "bash" | "sh" | "shell" | "zsh" | "console" => Some((SHELL_KEYWORDS, "#")),
+        "toml" | "yaml" | "yml" => Some((&[], "#")),
+        "json" | "c" | "cpp" | "java" => Some((&[], "//")),
+        _ => None,
+    }
+}
+
+/// Highlight one line of code: keywords, strings, numbers and line comments
+fn highlight_code(line: &str, lang: &str) -> Vec<StyledSpan> {
+    let Some((keywords, comment)) = language(lang) else {
+        return vec![StyledSpan::new(line, SpanKind::Code)];
+    };
+
+    let mut out = StyledLine::default();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(comment) {
+            out.push(rest, SpanKind::Comment);
+            break;
+        }
+
+        if c == '"' || c == '\'' || c == '`' {
+            // Up to and including the closing quote (or the end of the line)
+            let end = rest[1..]
+                .char_indices()
+                .scan(false, |escaped, (i, ch)| {
+                    let closes = !*escaped && ch == c;
+                    *escaped = !*escaped && ch == '\\';
+                    Some((i, closes))
+                })
+                .find(|(_, closes)| *closes)
+                .map(|(i, _)| i + 2)
+                .unwrap_or(rest.len());
+            out.push(&rest[..end], SpanKind::String);
+            rest = &rest[end..];
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let token = &rest[..end];
+            let kind = if c.is_ascii_digit() {
+                SpanKind::Number
+            } else if keywords.contains(&token) {
+                SpanKind::Keyword
+            } else {
+                SpanKind::Code
+            };
+            out.push(token, kind);
+            rest = &rest[end..];
+            continue;
+        }
+
+        out.push(&rest[..c.len_utf8()], SpanKind::Code);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out.spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[StyledLine]) -> Vec<String> {
+        lines.iter().map(|l| l.text()).collect()
+    }
+
+    #[test]
+    fn test_inline_emphasis_and_code() {
+        let spans = parse_inline("use **bold**, *italic* and `code`");
+        assert_eq!(
+            spans,
+            vec![
+                StyledSpan::new("use ", SpanKind::Text),
+                StyledSpan::new("bold", SpanKind::Bold),
+                StyledSpan::new(", ", SpanKind::Text),
+                StyledSpan::new("italic", SpanKind::Italic),
+                StyledSpan::new(" and ", SpanKind::Text),
+                StyledSpan::new("code", SpanKind::InlineCode),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_markers_are_text() {
+        assert_eq!(texts(&render("2 * 3 = 6 and a `tick", 80)), vec!["2 * 3 = 6 and a `tick"]);
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(plain_text("## Use **bold**\n- `x` here\n```\ncode\n```"), "Use bold\nx here\ncode");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let text = "# Plan\n\n- first step\n2. second\n> note\n---";
+        let lines = render(text, 10);
+        assert_eq!(
+            texts(&lines),
+            vec!["Plan", "", "• first", "  step", "2. second", "│ note", "──────────"]
+        );
+        assert_eq!(lines[0].spans[0].kind, SpanKind::Heading);
+        assert_eq!(lines[2].spans[0].kind, SpanKind::ListMarker);
+    }
+
+    #[test]
+    fn test_code_block() {
+        let text = "```rust\nlet x = \"hi\"; // greet\n```\nafter";
+        let lines = render(text, 80);
+        assert_eq!(texts(&lines), vec!["rust", "let x = \"hi\"; // greet", "after"]);
+        let kinds: Vec<SpanKind> = lines[1].spans.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![SpanKind::Keyword, SpanKind::Code, SpanKind::String, SpanKind::Code, SpanKind::Comment]
+        );
+    }
+
+    #[test]
+    fn test_to_html() {
+        let text = "# Plan\nSee **this** <b>\nand that\n\n- one\n- two\n```rust\nlet x = 1;\n```";
+        assert_eq!(
+            to_html(text),
+            "<h3>Plan</h3>\n<p>See <strong>this</strong> &lt;b&gt;\nand that</p>\n\
+             <ul><li>one</li><li>two</li></ul>\n\
+             <pre><code class=\"language-rust\"><span class=\"kw\">let</span> x = <span class=\"num\">1</span>;\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_code_keeps_indentation_and_breaks_long_lines() {
+        let lines = render("```\n    indented\nabcdef  ghij\n```", 6);
+        assert_eq!(texts(&lines), vec!["    in", "dented", "abcdef", "  ghij"]);
+    }
+}
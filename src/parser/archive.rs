@@ -0,0 +1,36 @@
+use crate::session::{ReadOutput, Session};
+use anyhow::{Context, Result};
+use chrono::Duration;
+use std::path::Path;
+
+use super::SessionParser;
+
+/// Parser for sessions imported with `recall import` (JSON transcripts in the archive)
+pub struct ArchiveParser;
+
+impl SessionParser for ArchiveParser {
+    fn can_parse(path: &Path) -> bool {
+        crate::archive::is_archived(path) && path.extension().is_some_and(|e| e == "json")
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let data = std::fs::read(path).context("Failed to open file")?;
+        let transcript: ReadOutput =
+            serde_json::from_slice(&data).context("Failed to parse archived transcript")?;
+
+        Ok(Session {
+            id: transcript.session_id,
+            source: transcript.source,
+            file_path: path.to_path_buf(),
+            cwd: transcript.cwd,
+            git_branch: transcript.git_branch,
+            title: transcript.title,
+            parent_id: transcript.parent_id,
+            is_subagent: transcript.is_subagent,
+            timestamp: transcript.timestamp,
+            start_timestamp: transcript.start_timestamp,
+            active_duration: Duration::seconds(transcript.active_duration_secs),
+            messages: transcript.messages,
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::session::{Message, MessageKind, Role, Session, SessionSource};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -6,12 +6,16 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{
+    anthropic_blocks, join_consecutive_messages, push_message, session_times, session_title,
+    SessionParser,
+};
 
 #[derive(Debug, Deserialize)]
 struct ClaudeLine {
     #[serde(rename = "type")]
     entry_type: String,
+    /// Session the entry was written in (a parent's ID in continued/forked files)
     #[serde(rename = "sessionId")]
     session_id: Option<String>,
     cwd: Option<String>,
@@ -28,6 +32,8 @@ struct ClaudeLine {
     /// Meta message flag (slash command prompt expansions)
     #[serde(rename = "isMeta")]
     is_meta: Option<bool>,
+    /// Conversation summary (on "summary" entries)
+    summary: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,9 +56,19 @@ impl SessionParser for ClaudeParser {
         let file = File::open(path).context("Failed to open file")?;
         let reader = BufReader::with_capacity(64 * 1024, file);
 
+        // ALWAYS use filename as session ID to avoid cross-contamination
+        // when a session is continued in a different directory (the continuation
+        // file will have the parent's sessionId in its content, but a different filename)
+        let session_id = path.file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
         let mut cwd: Option<String> = None;
         let mut git_branch: Option<String> = None;
         let mut latest_timestamp: Option<DateTime<Utc>> = None;
+        let mut summary: Option<String> = None;
+        let mut parent_id: Option<String> = None;
         let mut messages: Vec<Message> = Vec::new();
 
         for line in reader.lines() {
@@ -66,21 +82,42 @@ impl SessionParser for ClaudeParser {
                 Err(_) => continue, // Skip malformed lines
             };
 
+            // Resumed, compacted and forked sessions start with entries carried over
+            // from the session they continue, which keep that session's ID
+            if parent_id.is_none() {
+                if let Some(id) = entry.session_id.as_ref().filter(|id| **id != session_id) {
+                    parent_id = Some(id.clone());
+                }
+            }
+
+            // Summary entries title the conversation (the latest one wins)
+            if entry.entry_type == "summary" {
+                if entry.summary.is_some() {
+                    summary = entry.summary;
+                }
+                continue;
+            }
+
             // Skip non-message entries
             if entry.entry_type != "user" && entry.entry_type != "assistant" {
                 continue;
             }
 
-            // Skip synthetic messages (not actual user input):
-            // - Compaction summaries (v2.0.56+ isCompactSummary, v2.0.55 isVisibleInTranscriptOnly)
-            // - Slash command prompt expansions (isMeta)
-            if entry.is_compact_summary == Some(true)
-                || entry.is_visible_in_transcript_only == Some(true)
-                || entry.is_meta == Some(true)
-            {
+            // Skip slash command prompt expansions (isMeta, not actual user input)
+            if entry.is_meta == Some(true) {
                 continue;
             }
 
+            // Compaction summaries (v2.0.56+ isCompactSummary, v2.0.55 isVisibleInTranscriptOnly)
+            // are written as user messages, but aren't user input
+            let kind = if entry.is_compact_summary == Some(true)
+                || entry.is_visible_in_transcript_only == Some(true)
+            {
+                MessageKind::CompactionSummary
+            } else {
+                MessageKind::Turn
+            };
+
             // Extract session metadata from the first valid message
             if cwd.is_none() {
                 cwd = entry.cwd.clone();
@@ -110,79 +147,57 @@ impl SessionParser for ClaudeParser {
                     _ => continue,
                 };
 
-                let content = extract_content(&msg.content);
-                if content.is_empty() {
-                    continue;
-                }
+                let message = Message::new(role, anthropic_blocks(&msg.content), timestamp);
 
                 // Skip slash command expansions (internal Claude Code messages)
-                let trimmed = content.trim_start();
+                let trimmed = message.content.trim_start();
                 if trimmed.starts_with("<command-message>")
                     || trimmed.starts_with("<command-name>")
                 {
                     continue;
                 }
 
-                messages.push(Message {
-                    role,
-                    content,
-                    timestamp,
-                });
+                push_message(&mut messages, message.with_kind(kind));
             }
         }
 
-        // ALWAYS use filename as session ID to avoid cross-contamination
-        // when a session is continued in a different directory (the continuation
-        // file will have the parent's sessionId in its content, but a different filename)
-        let session_id = path.file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+        let timestamp = latest_timestamp.unwrap_or_else(Utc::now);
+        let (start_timestamp, active_duration) = session_times(&messages);
+        let title = session_title(summary, &messages);
 
         Ok(Session {
-            id: session_id,
+            id: session_id.clone(),
             source: SessionSource::ClaudeCode,
             file_path: path.to_path_buf(),
             cwd: cwd.unwrap_or_else(|| ".".to_string()),
             git_branch,
-            timestamp: latest_timestamp.unwrap_or_else(Utc::now),
+            title,
+            parent_id,
+            // Subagent (Task tool) transcripts are stored as agent-*.jsonl,
+            // with the sessionId of the session that ran them
+            is_subagent: session_id.starts_with("agent-"),
+            timestamp,
+            start_timestamp: start_timestamp.unwrap_or(timestamp),
+            active_duration,
             messages: join_consecutive_messages(messages),
         })
     }
 }
 
-/// Extract text content from Claude's message content field.
-/// - User messages: content is a plain string
-/// - Assistant messages: content is an array of {type, text} objects
-fn extract_content(content: &serde_json::Value) -> String {
-    match content {
-        // Direct string (user messages)
-        serde_json::Value::String(s) => s.clone(),
-
-        // Array of content blocks (assistant messages)
-        serde_json::Value::Array(arr) => {
-            let mut texts = Vec::new();
-            for item in arr {
-                if let Some(obj) = item.as_object() {
-                    // Only extract "text" type blocks, skip tool_use, thinking, etc.
-                    if obj.get("type").and_then(|v| v.as_str()) == Some("text") {
-                        if let Some(text) = obj.get("text").and_then(|v| v.as_str()) {
-                            texts.push(text.to_string());
-                        }
-                    }
-                }
-            }
-            texts.join("\n")
-        }
-
-        _ => String::new(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Plain-text content of a message with the given content field
+    fn extract_content(content: &serde_json::Value) -> String {
+        Message::new(Role::User, anthropic_blocks(content), Utc::now()).content
+    }
+
+    /// Reasoning of a message with the given content field
+    fn extract_thinking(content: &serde_json::Value) -> Option<String> {
+        Message::new(Role::Assistant, anthropic_blocks(content), Utc::now()).reasoning
+    }
+
     #[test]
     fn test_extract_content_string() {
         let content = serde_json::json!("Hello, world!");
@@ -199,4 +214,108 @@ mod tests {
         assert_eq!(extract_content(&content), "Hello\nWorld");
     }
 
+    #[test]
+    fn test_extract_thinking() {
+        let content = serde_json::json!([
+            {"type": "thinking", "thinking": "Check the config first", "signature": "x"},
+            {"type": "text", "text": "Done"}
+        ]);
+        assert_eq!(extract_thinking(&content).as_deref(), Some("Check the config first"));
+        assert_eq!(extract_content(&content), "Done");
+        assert_eq!(extract_thinking(&serde_json::json!("plain")), None);
+    }
+
+    #[test]
+    fn test_tool_calls_kept_as_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".claude/projects/-test");
+        std::fs::create_dir_all(&project).unwrap();
+        let path = project.join("abc.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"type":"user","cwd":"/p","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"run the tests"}}"#, "\n",
+                r#"{"type":"assistant","cwd":"/p","timestamp":"2025-01-01T00:00:01Z","message":{"role":"assistant","content":[{"type":"thinking","thinking":"Use cargo","signature":"x"},{"type":"text","text":"Running"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}}]}}"#, "\n",
+                r#"{"type":"user","cwd":"/p","timestamp":"2025-01-01T00:00:05Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#, "\n",
+                r#"{"type":"assistant","cwd":"/p","timestamp":"2025-01-01T00:00:06Z","message":{"role":"assistant","content":[{"type":"text","text":"All passed"}]}}"#, "\n",
+            ),
+        )
+        .unwrap();
+
+        let session = ClaudeParser::parse_file(&path).unwrap();
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.turn_count(), 2);
+        // The tool result goes with the reply that called the tool
+        let reply = &session.messages[1];
+        assert_eq!(reply.role, Role::Assistant);
+        assert_eq!(reply.content, "Running\n\nAll passed");
+        assert_eq!(reply.reasoning.as_deref(), Some("Use cargo"));
+        assert_eq!(reply.tool_names(), vec!["Bash"]);
+        assert_eq!(reply.blocks.len(), 5);
+    }
+
+    #[test]
+    fn test_summary_entry_sets_title() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".claude/projects/-test");
+        std::fs::create_dir_all(&project).unwrap();
+        let path = project.join("abc.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"type":"summary","summary":"Fixing the login flow","leafUuid":"1"}"#, "\n",
+                r#"{"type":"user","cwd":"/p","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"hi"}}"#, "\n",
+            ),
+        )
+        .unwrap();
+
+        let session = ClaudeParser::parse_file(&path).unwrap();
+        assert_eq!(session.title, "Fixing the login flow");
+        assert_eq!(session.messages.len(), 1);
+        assert_eq!(session.parent_id, None);
+    }
+
+    #[test]
+    fn test_compaction_summary_kept_as_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".claude/projects/-test");
+        std::fs::create_dir_all(&project).unwrap();
+        let path = project.join("abc.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"type":"user","isCompactSummary":true,"isVisibleInTranscriptOnly":true,"cwd":"/p","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"This session is being continued. Summary: fixed the parser"}}"#, "\n",
+                r#"{"type":"user","cwd":"/p","timestamp":"2025-01-01T00:00:01Z","message":{"role":"user","content":"now add tests"}}"#, "\n",
+                r#"{"type":"user","isMeta":true,"cwd":"/p","timestamp":"2025-01-01T00:00:02Z","message":{"role":"user","content":"expanded prompt"}}"#, "\n",
+            ),
+        )
+        .unwrap();
+
+        let session = ClaudeParser::parse_file(&path).unwrap();
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].kind, MessageKind::CompactionSummary);
+        assert_eq!(session.messages[1].kind, MessageKind::Turn);
+        assert_eq!(session.turn_count(), 1);
+        assert_eq!(session.title, "now add tests");
+    }
+
+    #[test]
+    fn test_continued_session_links_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".claude/projects/-test");
+        std::fs::create_dir_all(&project).unwrap();
+        let path = project.join("child.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"type":"user","sessionId":"parent","cwd":"/p","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"old"}}"#, "\n",
+                r#"{"type":"user","sessionId":"child","cwd":"/p","timestamp":"2025-01-02T00:00:00Z","message":{"role":"user","content":"new"}}"#, "\n",
+            ),
+        )
+        .unwrap();
+
+        let session = ClaudeParser::parse_file(&path).unwrap();
+        assert_eq!(session.id, "child");
+        assert_eq!(session.parent_id.as_deref(), Some("parent"));
+    }
 }
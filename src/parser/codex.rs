@@ -1,4 +1,4 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::session::{ContentBlock, Message, Role, Session, SessionSource};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -6,7 +6,7 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{join_consecutive_messages, push_message, session_times, session_title, SessionParser};
 
 #[derive(Debug, Deserialize)]
 struct CodexLine {
@@ -28,17 +28,33 @@ struct GitInfo {
     branch: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct ResponseItem {
+    #[serde(rename = "type")]
+    item_type: Option<String>,
     role: Option<String>,
-    content: Option<Vec<ContentBlock>>,
+    content: Option<Vec<CodexContent>>,
+    /// Reasoning summary (on "reasoning" items)
+    summary: Option<Vec<CodexContent>>,
+    /// Tool name (on tool call items)
+    name: Option<String>,
+    /// JSON-encoded tool arguments (on "function_call" items)
+    arguments: Option<String>,
+    /// Tool input (on "custom_tool_call" items)
+    input: Option<serde_json::Value>,
+    /// Shell command (on "local_shell_call" items)
+    action: Option<serde_json::Value>,
+    /// Tool output (on tool call output items)
+    output: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ContentBlock {
+#[derive(Debug, Default, Deserialize)]
+struct CodexContent {
     #[serde(rename = "type")]
     content_type: String,
     text: Option<String>,
+    /// Image URL (on "input_image" blocks)
+    image_url: Option<String>,
 }
 
 pub struct CodexParser;
@@ -60,6 +76,7 @@ impl SessionParser for CodexParser {
         let mut git_branch: Option<String> = None;
         let mut latest_timestamp: Option<DateTime<Utc>> = None;
         let mut messages: Vec<Message> = Vec::new();
+        let mut pending_reasoning: Vec<ContentBlock> = Vec::new();
 
         for line in reader.lines() {
             let line = line.context("Failed to read line")?;
@@ -100,42 +117,42 @@ impl SessionParser for CodexParser {
                 "response_item" => {
                     if let Some(payload) = &entry.payload {
                         if let Ok(item) = serde_json::from_value::<ResponseItem>(payload.clone()) {
-                            let role = match item.role.as_deref() {
-                                Some("user") => Role::User,
-                                Some("assistant") => Role::Assistant,
-                                _ => {
-                                    // Try to infer role from content type
-                                    if let Some(content) = &item.content {
-                                        if content.iter().any(|c| c.content_type == "input_text") {
-                                            Role::User
-                                        } else if content
-                                            .iter()
-                                            .any(|c| c.content_type == "output_text")
-                                        {
-                                            Role::Assistant
-                                        } else {
-                                            continue;
-                                        }
-                                    } else {
-                                        continue;
-                                    }
+                            let (role, blocks) = match item.item_type.as_deref() {
+                                // Reasoning precedes the reply it led to
+                                Some("reasoning") => {
+                                    pending_reasoning.extend(
+                                        extract_codex_reasoning(&item)
+                                            .into_iter()
+                                            .map(|text| ContentBlock::Reasoning { text }),
+                                    );
+                                    continue;
+                                }
+                                Some("function_call" | "custom_tool_call" | "local_shell_call") => {
+                                    (Role::Assistant, vec![codex_tool_use(&item)])
+                                }
+                                Some("function_call_output" | "custom_tool_call_output") => {
+                                    (Role::Assistant, vec![codex_tool_result(&item)])
                                 }
+                                _ => match codex_message_role(&item) {
+                                    Some(role) => (role, extract_codex_blocks(&item)),
+                                    None => continue,
+                                },
                             };
 
-                            let content = extract_codex_content(&item);
-                            if !content.is_empty() {
-                                messages.push(Message {
-                                    role,
-                                    content,
-                                    timestamp,
-                                });
-
-                                // Update latest timestamp
-                                if latest_timestamp.is_none()
-                                    || timestamp > latest_timestamp.unwrap()
-                                {
-                                    latest_timestamp = Some(timestamp);
-                                }
+                            let mut message = Message::new(role, blocks, timestamp);
+                            if message.blocks.is_empty() {
+                                continue;
+                            }
+                            if role == Role::Assistant && !pending_reasoning.is_empty() {
+                                let mut blocks = std::mem::take(&mut pending_reasoning);
+                                blocks.append(&mut message.blocks);
+                                message = Message::new(role, blocks, timestamp);
+                            }
+                            push_message(&mut messages, message);
+
+                            // Update latest timestamp
+                            if latest_timestamp.is_none() || timestamp > latest_timestamp.unwrap() {
+                                latest_timestamp = Some(timestamp);
                             }
                         }
                     }
@@ -152,27 +169,89 @@ impl SessionParser for CodexParser {
                 .to_string()
         });
 
+        let timestamp = latest_timestamp.unwrap_or_else(Utc::now);
+        let (start_timestamp, active_duration) = session_times(&messages);
+        let title = session_title(None, &messages);
+
         Ok(Session {
             id: session_id,
             source: SessionSource::CodexCli,
             file_path: path.to_path_buf(),
             cwd: cwd.unwrap_or_else(|| ".".to_string()),
             git_branch,
-            timestamp: latest_timestamp.unwrap_or_else(Utc::now),
+            title,
+            parent_id: None,
+            is_subagent: false,
+            timestamp,
+            start_timestamp: start_timestamp.unwrap_or(timestamp),
+            active_duration,
             messages: join_consecutive_messages(messages),
         })
     }
 }
 
-/// Extract text content from a Codex response item.
+/// Get the role of a Codex message item, inferring it from the content type if missing
+fn codex_message_role(item: &ResponseItem) -> Option<Role> {
+    match item.role.as_deref() {
+        Some("user") => Some(Role::User),
+        Some("assistant") => Some(Role::Assistant),
+        _ => {
+            let content = item.content.as_ref()?;
+            if content.iter().any(|c| c.content_type == "input_text") {
+                Some(Role::User)
+            } else if content.iter().any(|c| c.content_type == "output_text") {
+                Some(Role::Assistant)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Get the tool call of a Codex tool call item
+fn codex_tool_use(item: &ResponseItem) -> ContentBlock {
+    let input = if let Some(arguments) = &item.arguments {
+        serde_json::from_str(arguments).unwrap_or_else(|_| arguments.clone().into())
+    } else {
+        item.input.clone().or_else(|| item.action.clone()).unwrap_or_default()
+    };
+    let name = item.name.clone().unwrap_or_else(|| match item.item_type.as_deref() {
+        Some("local_shell_call") => "shell".to_string(),
+        _ => "tool".to_string(),
+    });
+    ContentBlock::ToolUse { name, input }
+}
+
+/// Get the output of a Codex tool call output item
+fn codex_tool_result(item: &ResponseItem) -> ContentBlock {
+    let output = match &item.output {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+        None => String::new(),
+    };
+    ContentBlock::ToolResult {
+        output,
+        is_error: false,
+    }
+}
+
+/// Extract content blocks from a Codex message item.
 /// Filters out CLI-injected blocks (AGENTS.md instructions, environment_context).
-fn extract_codex_content(item: &ResponseItem) -> String {
+fn extract_codex_blocks(item: &ResponseItem) -> Vec<ContentBlock> {
     let Some(content) = &item.content else {
-        return String::new();
+        return Vec::new();
     };
 
-    let mut texts = Vec::new();
+    let mut blocks = Vec::new();
     for block in content {
+        if block.content_type == "input_image" {
+            blocks.push(ContentBlock::Image {
+                media_type: None,
+                source: block.image_url.clone().filter(|url| !url.starts_with("data:")),
+            });
+            continue;
+        }
+
         // Extract from input_text or output_text blocks
         if (block.content_type == "input_text" || block.content_type == "output_text")
             && block.text.is_some()
@@ -196,47 +275,83 @@ fn extract_codex_content(item: &ResponseItem) -> String {
                 {
                     continue;
                 }
-                texts.push(text.clone());
+                blocks.push(ContentBlock::Text { text: text.clone() });
             }
         }
     }
-    texts.join("\n")
+    blocks
+}
+
+/// Extract the summary text of a Codex reasoning item
+fn extract_codex_reasoning(item: &ResponseItem) -> Vec<String> {
+    item.summary
+        .iter()
+        .flatten()
+        .filter_map(|block| block.text.clone())
+        .filter(|text| !text.trim().is_empty())
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Plain-text content of a Codex message item
+    fn extract_codex_content(item: &ResponseItem) -> String {
+        Message::new(Role::User, extract_codex_blocks(item), Utc::now()).content
+    }
+
     #[test]
     fn test_extract_codex_content() {
         let item = ResponseItem {
+            item_type: Some("message".to_string()),
             role: Some("user".to_string()),
-            content: Some(vec![ContentBlock {
+            content: Some(vec![CodexContent {
                 content_type: "input_text".to_string(),
                 text: Some("Hello Codex".to_string()),
+                ..Default::default()
             }]),
+            ..Default::default()
         };
         assert_eq!(extract_codex_content(&item), "Hello Codex");
     }
 
+    #[test]
+    fn test_extract_codex_reasoning() {
+        let item: ResponseItem = serde_json::from_value(serde_json::json!({
+            "type": "reasoning",
+            "summary": [{"type": "summary_text", "text": "**Planning** the search"}],
+            "content": null,
+            "encrypted_content": "gAAA"
+        }))
+        .unwrap();
+        assert_eq!(extract_codex_reasoning(&item), vec!["**Planning** the search"]);
+        assert_eq!(extract_codex_content(&item), "");
+    }
+
     #[test]
     fn test_extract_codex_content_filters_injected_blocks() {
         let item = ResponseItem {
+            item_type: Some("message".to_string()),
             role: Some("user".to_string()),
             content: Some(vec![
-                ContentBlock {
+                CodexContent {
                     content_type: "input_text".to_string(),
                     text: Some("# AGENTS.md instructions for /some/path\n\n<INSTRUCTIONS>\nsome instructions\n</INSTRUCTIONS>".to_string()),
+                    ..Default::default()
                 },
-                ContentBlock {
+                CodexContent {
                     content_type: "input_text".to_string(),
                     text: Some("<environment_context>\n  <cwd>/some/path</cwd>\n</environment_context>".to_string()),
+                    ..Default::default()
                 },
-                ContentBlock {
+                CodexContent {
                     content_type: "input_text".to_string(),
                     text: Some("actual user message".to_string()),
+                    ..Default::default()
                 },
             ]),
+            ..Default::default()
         };
         assert_eq!(extract_codex_content(&item), "actual user message");
     }
@@ -245,15 +360,48 @@ mod tests {
     fn test_extract_codex_content_keeps_partial_tags() {
         // User might ask about these tags - don't filter if not properly closed
         let item = ResponseItem {
+            item_type: Some("message".to_string()),
             role: Some("user".to_string()),
-            content: Some(vec![ContentBlock {
+            content: Some(vec![CodexContent {
                 content_type: "input_text".to_string(),
                 text: Some("<environment_context> what is this?".to_string()),
+                ..Default::default()
             }]),
+            ..Default::default()
         };
         assert_eq!(
             extract_codex_content(&item),
             "<environment_context> what is this?"
         );
     }
+
+    #[test]
+    fn test_tool_calls_kept_as_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = dir.path().join(".codex/sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        let path = sessions.join("rollout-1.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"timestamp":"2025-01-01T00:00:00Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"list files"}]}}"#, "\n",
+                r#"{"timestamp":"2025-01-01T00:00:01Z","type":"response_item","payload":{"type":"reasoning","summary":[{"type":"summary_text","text":"Use ls"}]}}"#, "\n",
+                r#"{"timestamp":"2025-01-01T00:00:02Z","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"ls\"]}","call_id":"c1"}}"#, "\n",
+                r#"{"timestamp":"2025-01-01T00:00:03Z","type":"response_item","payload":{"type":"function_call_output","call_id":"c1","output":"a.rs"}}"#, "\n",
+                r#"{"timestamp":"2025-01-01T00:00:04Z","type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"One file: a.rs"}]}}"#, "\n",
+            ),
+        )
+        .unwrap();
+
+        let session = CodexParser::parse_file(&path).unwrap();
+        assert_eq!(session.messages.len(), 2);
+        let reply = &session.messages[1];
+        assert_eq!(reply.content, "One file: a.rs");
+        assert_eq!(reply.reasoning.as_deref(), Some("Use ls"));
+        assert_eq!(
+            reply.blocks[1],
+            ContentBlock::ToolUse { name: "shell".to_string(), input: serde_json::json!({"command": ["ls"]}) }
+        );
+        assert_eq!(reply.blocks.len(), 4);
+    }
 }
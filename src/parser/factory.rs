@@ -1,4 +1,4 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::session::{ContentBlock, Message, Role, Session, SessionSource};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -6,14 +6,16 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{
+    anthropic_blocks, join_consecutive_messages, push_message, session_times, session_title,
+    SessionParser,
+};
 
 #[derive(Debug, Deserialize)]
 struct FactoryLine {
     #[serde(rename = "type")]
     entry_type: String,
     id: Option<String>,
-    #[allow(dead_code)]
     title: Option<String>,
     cwd: Option<String>,
     timestamp: Option<String>,
@@ -42,6 +44,7 @@ impl SessionParser for FactoryParser {
 
         let mut session_id: Option<String> = None;
         let mut cwd: Option<String> = None;
+        let mut title: Option<String> = None;
         let mut latest_timestamp: Option<DateTime<Utc>> = None;
         let mut messages: Vec<Message> = Vec::new();
 
@@ -65,6 +68,9 @@ impl SessionParser for FactoryParser {
                     if cwd.is_none() {
                         cwd = entry.cwd.clone();
                     }
+                    if title.is_none() {
+                        title = entry.title.clone();
+                    }
                 }
                 "message" => {
                     // Parse timestamp
@@ -88,14 +94,8 @@ impl SessionParser for FactoryParser {
                             _ => continue,
                         };
 
-                        let content = extract_content(&msg.content);
-                        if !content.is_empty() {
-                            messages.push(Message {
-                                role,
-                                content,
-                                timestamp,
-                            });
-                        }
+                        let blocks = extract_blocks(&msg.content);
+                        push_message(&mut messages, Message::new(role, blocks, timestamp));
                     }
                 }
                 _ => {}
@@ -115,46 +115,42 @@ impl SessionParser for FactoryParser {
                 .to_string()
         });
 
+        let timestamp = latest_timestamp.unwrap_or_else(Utc::now);
+        let (start_timestamp, active_duration) = session_times(&messages);
+        let title = session_title(title, &messages);
+
         Ok(Session {
             id: session_id,
             source: SessionSource::Factory,
             file_path: path.to_path_buf(),
             cwd: cwd.unwrap_or_else(|| ".".to_string()),
             git_branch: None,
-            timestamp: latest_timestamp.unwrap_or_else(Utc::now),
+            title,
+            parent_id: None,
+            is_subagent: false,
+            timestamp,
+            start_timestamp: start_timestamp.unwrap_or(timestamp),
+            active_duration,
             messages: join_consecutive_messages(messages),
         })
     }
 }
 
-/// Extract text content from Factory's message content field.
-/// Content is an array of {type, text} objects.
+/// Extract content blocks from Factory's message content field.
+/// Content is an array of {type, ...} objects, like Claude's.
 /// Filters out system-reminder blocks which are injected by the CLI.
-fn extract_content(content: &serde_json::Value) -> String {
-    let serde_json::Value::Array(arr) = content else {
-        return String::new();
-    };
-
-    let mut texts = Vec::new();
-    for item in arr {
-        if let Some(obj) = item.as_object() {
-            // Only extract "text" type blocks, skip tool_use, tool_result, etc.
-            if obj.get("type").and_then(|v| v.as_str()) == Some("text") {
-                if let Some(text) = obj.get("text").and_then(|v| v.as_str()) {
-                    // Skip system-reminder blocks (injected by CLI, not user input)
-                    // Must have both opening and closing tags to filter
-                    let trimmed = text.trim();
-                    if trimmed.starts_with("<system-reminder>")
-                        && trimmed.ends_with("</system-reminder>")
-                    {
-                        continue;
-                    }
-                    texts.push(text.to_string());
-                }
-            }
+fn extract_blocks(content: &serde_json::Value) -> Vec<ContentBlock> {
+    let mut blocks = anthropic_blocks(content);
+    blocks.retain(|block| match block {
+        // Skip system-reminder blocks (injected by CLI, not user input)
+        // Must have both opening and closing tags to filter
+        ContentBlock::Text { text } => {
+            let trimmed = text.trim();
+            !(trimmed.starts_with("<system-reminder>") && trimmed.ends_with("</system-reminder>"))
         }
-    }
-    texts.join("\n")
+        _ => true,
+    });
+    blocks
 }
 
 /// Extract cwd from Factory's directory structure.
@@ -178,6 +174,11 @@ fn extract_cwd_from_path(path: &Path) -> Option<String> {
 mod tests {
     use super::*;
 
+    /// Plain-text content of a message with the given content field
+    fn extract_content(content: &serde_json::Value) -> String {
+        Message::new(Role::User, extract_blocks(content), Utc::now()).content
+    }
+
     #[test]
     fn test_extract_content() {
         let content = serde_json::json!([
@@ -1,25 +1,113 @@
+mod archive;
 mod claude;
 mod codex;
 mod factory;
 mod opencode;
 
+pub use archive::ArchiveParser;
 pub use claude::ClaudeParser;
 pub use codex::CodexParser;
 pub use factory::FactoryParser;
 pub use opencode::OpenCodeParser;
 
-use crate::session::{Message, Session};
+use crate::ignore::IgnoreRules;
+use crate::session::{ContentBlock, Message, Role, Session, SessionSource};
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
 use std::path::Path;
 
+/// Gaps between messages longer than this are idle time, not active time
+const IDLE_GAP: Duration = Duration::minutes(30);
+
+/// Maximum length of a session title, in characters
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Add a parsed message to a session's messages, skipping messages with nothing in them.
+/// Tool results sent back in a user message (Claude Code, Factory) go with the agent
+/// message that called the tools, so they don't split the agent's reply.
+pub fn push_message(messages: &mut Vec<Message>, message: Message) {
+    if message.blocks.is_empty() {
+        return;
+    }
+    if message.role == Role::User && message.only_tool_results() {
+        if let Some(last) = messages.last_mut().filter(|m| m.role == Role::Assistant) {
+            last.blocks.extend(message.blocks);
+            return;
+        }
+    }
+    messages.push(message);
+}
+
+/// Parse an Anthropic-style content field (Claude Code, Factory): a plain string,
+/// or an array of typed blocks
+pub fn anthropic_blocks(content: &serde_json::Value) -> Vec<ContentBlock> {
+    let serde_json::Value::Array(arr) = content else {
+        return match content.as_str() {
+            Some(text) if !text.is_empty() => vec![ContentBlock::Text {
+                text: text.to_string(),
+            }],
+            _ => Vec::new(),
+        };
+    };
+
+    let str_field = |item: &serde_json::Value, key: &str| {
+        item.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+    };
+
+    arr.iter()
+        .filter_map(|item| match item.get("type").and_then(|v| v.as_str())? {
+            "text" => str_field(item, "text")
+                .filter(|t| !t.is_empty())
+                .map(|text| ContentBlock::Text { text }),
+            "thinking" => str_field(item, "thinking")
+                .filter(|t| !t.trim().is_empty())
+                .map(|text| ContentBlock::Reasoning { text }),
+            "tool_use" => Some(ContentBlock::ToolUse {
+                name: str_field(item, "name").unwrap_or_default(),
+                input: item.get("input").cloned().unwrap_or_default(),
+            }),
+            "tool_result" => Some(ContentBlock::ToolResult {
+                output: item
+                    .get("content")
+                    .map(|c| {
+                        anthropic_blocks(c)
+                            .into_iter()
+                            .filter_map(|b| match b {
+                                ContentBlock::Text { text } => Some(text),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default(),
+                is_error: item.get("is_error").and_then(|v| v.as_bool()).unwrap_or(false),
+            }),
+            "image" => {
+                let source = item.get("source");
+                Some(ContentBlock::Image {
+                    media_type: source.and_then(|s| str_field(s, "media_type")),
+                    source: source.and_then(|s| str_field(s, "url")),
+                })
+            }
+            "document" => Some(ContentBlock::Attachment {
+                path: str_field(item, "title").unwrap_or_else(|| "document".to_string()),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Join consecutive messages from the same role into single messages.
 /// Uses the latest timestamp when joining.
 pub fn join_consecutive_messages(messages: Vec<Message>) -> Vec<Message> {
     messages.into_iter().fold(Vec::new(), |mut acc, msg| {
         if let Some(last) = acc.last_mut() {
-            if last.role == msg.role {
-                last.content.push_str("\n\n");
-                last.content.push_str(&msg.content);
+            if last.role == msg.role && last.kind == msg.kind {
+                append_paragraph(&mut last.content, &msg.content);
+                if let Some(reasoning) = msg.reasoning {
+                    append_paragraph(last.reasoning.get_or_insert_with(String::new), &reasoning);
+                }
+                last.blocks.extend(msg.blocks);
                 last.timestamp = msg.timestamp; // use latest
                 return acc;
             }
@@ -29,6 +117,55 @@ pub fn join_consecutive_messages(messages: Vec<Message>) -> Vec<Message> {
     })
 }
 
+/// Append text as a new paragraph (reasoning-only messages have empty content)
+fn append_paragraph(text: &mut String, paragraph: &str) {
+    if paragraph.is_empty() {
+        return;
+    }
+    if !text.is_empty() {
+        text.push_str("\n\n");
+    }
+    text.push_str(paragraph);
+}
+
+/// Get a session's first message time and its active duration (idle gaps excluded).
+/// Call before joining messages, since joining keeps only the latest timestamp.
+pub fn session_times(messages: &[Message]) -> (Option<DateTime<Utc>>, Duration) {
+    let mut timestamps: Vec<DateTime<Utc>> = messages.iter().map(|m| m.timestamp).collect();
+    timestamps.sort();
+
+    let active = timestamps
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|gap| *gap <= IDLE_GAP)
+        .fold(Duration::zero(), |total, gap| total + gap);
+
+    (timestamps.first().copied(), active)
+}
+
+/// Get a session's title: the agent-provided one if any, otherwise the first
+/// line of the first user prompt (whitespace collapsed, truncated)
+pub fn session_title(explicit: Option<String>, messages: &[Message]) -> String {
+    let title = explicit
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| {
+            messages
+                .iter()
+                .find(|m| m.is_user_turn())
+                .and_then(|m| m.content.lines().find(|l| !l.trim().is_empty()))
+                .map(|l| l.to_string())
+        })
+        .unwrap_or_default();
+
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.chars().count() > MAX_TITLE_CHARS {
+        let truncated: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+        format!("{}…", truncated.trim_end())
+    } else {
+        title
+    }
+}
+
 /// Trait for parsing session files
 pub trait SessionParser {
     /// Parse a session file into a Session
@@ -38,32 +175,22 @@ pub trait SessionParser {
     fn can_parse(path: &Path) -> bool;
 }
 
-/// Discover all session files from Claude Code, Codex CLI, and Factory
-pub fn discover_session_files() -> Vec<std::path::PathBuf> {
+/// Discover all session files from Claude Code, Codex CLI, Factory and OpenCode (plus
+/// imported archives), skipping ignored sources and session IDs
+pub fn discover_session_files(ignore: &IgnoreRules) -> Vec<std::path::PathBuf> {
     let mut files = Vec::new();
 
-    // Allow override for testing
-    let home = std::env::var("RECALL_HOME_OVERRIDE")
-        .map(std::path::PathBuf::from)
-        .ok()
-        .or_else(dirs::home_dir);
-
-    if let Some(home) = home {
+    if let Some(home) = crate::paths::home_dir() {
         // Claude Code: ~/.claude/projects/*/*.jsonl
         let claude_dir = home.join(".claude/projects");
-        if claude_dir.exists() {
+        if claude_dir.exists() && !ignore.ignores_source(SessionSource::ClaudeCode) {
             if let Ok(projects) = std::fs::read_dir(&claude_dir) {
                 for project in projects.flatten() {
                     if let Ok(sessions) = std::fs::read_dir(project.path()) {
                         for session in sessions.flatten() {
                             let path = session.path();
+                            // Includes agent-*.jsonl subagent (sidechain) transcripts
                             if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-                                // Skip agent sidechain files (internal subagent conversations)
-                                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                                    if name.starts_with("agent-") {
-                                        continue;
-                                    }
-                                }
                                 files.push(path);
                             }
                         }
@@ -74,7 +201,7 @@ pub fn discover_session_files() -> Vec<std::path::PathBuf> {
 
         // Codex CLI: ~/.codex/sessions/**/*.jsonl
         let codex_dir = home.join(".codex/sessions");
-        if codex_dir.exists() {
+        if codex_dir.exists() && !ignore.ignores_source(SessionSource::CodexCli) {
             for entry in walkdir::WalkDir::new(&codex_dir)
                 .into_iter()
                 .flatten()
@@ -88,7 +215,7 @@ pub fn discover_session_files() -> Vec<std::path::PathBuf> {
 
         // Factory: ~/.factory/sessions/**/*.jsonl
         let factory_dir = home.join(".factory/sessions");
-        if factory_dir.exists() {
+        if factory_dir.exists() && !ignore.ignores_source(SessionSource::Factory) {
             for entry in walkdir::WalkDir::new(&factory_dir)
                 .into_iter()
                 .flatten()
@@ -102,7 +229,7 @@ pub fn discover_session_files() -> Vec<std::path::PathBuf> {
 
         // OpenCode: ~/.local/share/opencode/storage/session/**/*.json
         let opencode_dir = home.join(".local/share/opencode/storage/session");
-        if opencode_dir.exists() {
+        if opencode_dir.exists() && !ignore.ignores_source(SessionSource::OpenCode) {
             for entry in walkdir::WalkDir::new(&opencode_dir)
                 .into_iter()
                 .flatten()
@@ -120,12 +247,29 @@ pub fn discover_session_files() -> Vec<std::path::PathBuf> {
         }
     }
 
+    // Sessions imported with `recall import`: <archive>/<bundle>/sessions/<source>/*.json
+    let archive_dir = crate::archive::archive_dir();
+    if archive_dir.exists() {
+        for entry in walkdir::WalkDir::new(&archive_dir)
+            .into_iter()
+            .flatten()
+        {
+            let path = entry.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                files.push(path.to_path_buf());
+            }
+        }
+    }
+
+    files.retain(|f| !ignore.ignores_file(f));
     files
 }
 
 /// Parse a session file, auto-detecting the format
 pub fn parse_session_file(path: &Path) -> Result<Session> {
-    if ClaudeParser::can_parse(path) {
+    if ArchiveParser::can_parse(path) {
+        ArchiveParser::parse_file(path)
+    } else if ClaudeParser::can_parse(path) {
         ClaudeParser::parse_file(path)
     } else if CodexParser::can_parse(path) {
         CodexParser::parse_file(path)
@@ -141,16 +285,16 @@ pub fn parse_session_file(path: &Path) -> Result<Session> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::session::Role;
+    use crate::session::{ContentBlock, MessageKind, Role};
     use chrono::Utc;
 
     #[test]
     fn test_join_consecutive_messages_different_roles() {
         let now = Utc::now();
         let messages = vec![
-            Message { role: Role::User, content: "Hello".to_string(), timestamp: now },
-            Message { role: Role::Assistant, content: "Hi".to_string(), timestamp: now },
-            Message { role: Role::User, content: "Bye".to_string(), timestamp: now },
+            Message::text(Role::User, "Hello", now),
+            Message::text(Role::Assistant, "Hi", now),
+            Message::text(Role::User, "Bye", now),
         ];
         let joined = join_consecutive_messages(messages);
         assert_eq!(joined.len(), 3);
@@ -161,9 +305,9 @@ mod tests {
         let t1 = Utc::now();
         let t2 = t1 + chrono::Duration::seconds(10);
         let messages = vec![
-            Message { role: Role::User, content: "Part 1".to_string(), timestamp: t1 },
-            Message { role: Role::User, content: "Part 2".to_string(), timestamp: t2 },
-            Message { role: Role::Assistant, content: "Response".to_string(), timestamp: t2 },
+            Message::text(Role::User, "Part 1", t1),
+            Message::text(Role::User, "Part 2", t2),
+            Message::text(Role::Assistant, "Response", t2),
         ];
         let joined = join_consecutive_messages(messages);
         assert_eq!(joined.len(), 2);
@@ -176,12 +320,138 @@ mod tests {
     fn test_join_consecutive_messages_multiple_same_role() {
         let now = Utc::now();
         let messages = vec![
-            Message { role: Role::Assistant, content: "A".to_string(), timestamp: now },
-            Message { role: Role::Assistant, content: "B".to_string(), timestamp: now },
-            Message { role: Role::Assistant, content: "C".to_string(), timestamp: now },
+            Message::text(Role::Assistant, "A", now),
+            Message::text(Role::Assistant, "B", now),
+            Message::text(Role::Assistant, "C", now),
         ];
         let joined = join_consecutive_messages(messages);
         assert_eq!(joined.len(), 1);
         assert_eq!(joined[0].content, "A\n\nB\n\nC");
     }
+
+    #[test]
+    fn test_join_consecutive_messages_merges_reasoning() {
+        let now = Utc::now();
+        let messages = vec![
+            Message::new(Role::Assistant, vec![ContentBlock::Reasoning { text: "Think".to_string() }], now),
+            Message::text(Role::Assistant, "Answer", now),
+            Message::new(
+                Role::Assistant,
+                vec![
+                    ContentBlock::Reasoning { text: "Again".to_string() },
+                    ContentBlock::Text { text: "More".to_string() },
+                ],
+                now,
+            ),
+        ];
+        let joined = join_consecutive_messages(messages);
+        assert_eq!(joined.len(), 1);
+        assert_eq!(joined[0].content, "Answer\n\nMore");
+        assert_eq!(joined[0].reasoning.as_deref(), Some("Think\n\nAgain"));
+    }
+
+    #[test]
+    fn test_join_consecutive_messages_keeps_compaction_summaries_apart() {
+        let now = Utc::now();
+        let messages = vec![
+            Message::text(Role::User, "Summary", now).with_kind(MessageKind::CompactionSummary),
+            Message::text(Role::User, "Go on", now),
+        ];
+        let joined = join_consecutive_messages(messages);
+        assert_eq!(joined.len(), 2);
+        assert_eq!(joined[0].kind, MessageKind::CompactionSummary);
+
+        // ...and don't count as the first prompt
+        assert_eq!(session_title(None, &joined), "Go on");
+    }
+
+    #[test]
+    fn test_anthropic_blocks() {
+        let content = serde_json::json!([
+            {"type": "thinking", "thinking": "Read it first", "signature": "x"},
+            {"type": "text", "text": "Reading"},
+            {"type": "tool_use", "id": "t1", "name": "Read", "input": {"file_path": "a.rs"}},
+            {"type": "tool_result", "tool_use_id": "t1", "content": [{"type": "text", "text": "fn main() {}"}]},
+            {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBOR"}}
+        ]);
+        assert_eq!(
+            anthropic_blocks(&content),
+            vec![
+                ContentBlock::Reasoning { text: "Read it first".to_string() },
+                ContentBlock::Text { text: "Reading".to_string() },
+                ContentBlock::ToolUse { name: "Read".to_string(), input: serde_json::json!({"file_path": "a.rs"}) },
+                ContentBlock::ToolResult { output: "fn main() {}".to_string(), is_error: false },
+                ContentBlock::Image { media_type: Some("image/png".to_string()), source: None },
+            ]
+        );
+        assert_eq!(anthropic_blocks(&serde_json::json!("hi")), vec![ContentBlock::Text { text: "hi".to_string() }]);
+    }
+
+    #[test]
+    fn test_push_message_folds_tool_results_into_agent_reply() {
+        let now = Utc::now();
+        let mut messages = Vec::new();
+        let result = ContentBlock::ToolResult { output: "ok".to_string(), is_error: false };
+        // Without an agent message to go with, a tool result keeps its own message
+        push_message(&mut messages, Message::new(Role::User, vec![result.clone()], now));
+        push_message(&mut messages, Message::text(Role::Assistant, "Running it", now));
+        push_message(&mut messages, Message::new(Role::User, vec![result], now));
+        push_message(&mut messages, Message::new(Role::User, Vec::new(), now));
+        push_message(&mut messages, Message::text(Role::Assistant, "Passed", now));
+
+        let roles: Vec<Role> = messages.iter().map(|m| m.role).collect();
+        assert_eq!(roles, vec![Role::User, Role::Assistant, Role::Assistant]);
+        assert!(messages[0].only_tool_results());
+        assert_eq!(messages[1].content, "Running it");
+        assert_eq!(messages[1].blocks.len(), 2);
+
+        let joined = join_consecutive_messages(messages);
+        assert_eq!(joined.len(), 2);
+        assert_eq!(joined[1].content, "Running it\n\nPassed");
+    }
+
+    #[test]
+    fn test_session_times_excludes_idle_gaps() {
+        let t0 = Utc::now();
+        let messages = vec![
+            Message::text(Role::User, "A", t0),
+            Message::text(Role::Assistant, "B", t0 + Duration::minutes(5)),
+            // Came back the next day
+            Message::text(Role::User, "C", t0 + Duration::hours(20)),
+            Message::text(Role::Assistant, "D", t0 + Duration::hours(20) + Duration::minutes(2)),
+        ];
+        let (start, active) = session_times(&messages);
+        assert_eq!(start, Some(t0));
+        assert_eq!(active, Duration::minutes(7));
+    }
+
+    #[test]
+    fn test_session_title_prefers_explicit() {
+        let messages = vec![
+            Message::text(Role::User, "Fix the login bug", Utc::now()),
+        ];
+        assert_eq!(session_title(Some("Login fix".to_string()), &messages), "Login fix");
+        assert_eq!(session_title(Some("  ".to_string()), &messages), "Fix the login bug");
+    }
+
+    #[test]
+    fn test_session_title_from_first_prompt() {
+        let messages = vec![
+            Message::text(Role::Assistant, "Welcome", Utc::now()),
+            Message::text(Role::User, "\n  Deploy   to staging\nThen run tests", Utc::now()),
+        ];
+        assert_eq!(session_title(None, &messages), "Deploy to staging");
+
+        let long = Message::text(Role::User, "word ".repeat(40), Utc::now());
+        let title = session_title(None, &[long]);
+        assert_eq!(title.chars().count(), MAX_TITLE_CHARS);
+        assert!(title.ends_with('…'));
+    }
+
+    #[test]
+    fn test_session_times_empty() {
+        let (start, active) = session_times(&[]);
+        assert_eq!(start, None);
+        assert_eq!(active, Duration::zero());
+    }
 }
@@ -1,4 +1,4 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::session::{ContentBlock, Message, Role, Session, SessionSource};
 use anyhow::{Context, Result};
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
@@ -6,7 +6,7 @@ use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{join_consecutive_messages, push_message, session_times, session_title, SessionParser};
 
 /// OpenCode session metadata from session/<project_id>/ses_*.json
 #[derive(Debug, Deserialize)]
@@ -16,7 +16,6 @@ struct OpenCodeSession {
     #[allow(dead_code)]
     project_id: Option<String>,
     directory: Option<String>,
-    #[allow(dead_code)]
     title: Option<String>,
     time: Option<TimeInfo>,
 }
@@ -60,6 +59,22 @@ struct OpenCodePart {
     #[serde(rename = "type")]
     part_type: String,
     text: Option<String>,
+    /// Tool name (on "tool" parts)
+    tool: Option<String>,
+    /// Tool call input, output and status (on "tool" parts)
+    state: Option<ToolState>,
+    /// MIME type, name and URL of an attached file (on "file" parts)
+    mime: Option<String>,
+    filename: Option<String>,
+    url: Option<String>,
+}
+
+/// State of a tool call part
+#[derive(Debug, Deserialize)]
+struct ToolState {
+    status: Option<String>,
+    input: Option<serde_json::Value>,
+    output: Option<String>,
 }
 
 pub struct OpenCodeParser;
@@ -142,30 +157,28 @@ impl SessionParser for OpenCodeParser {
                 };
 
                 // Read parts for this message
-                let content = read_message_parts(&storage_root, &msg.id);
-                if !content.is_empty() {
-                    messages.push(Message {
-                        role,
-                        content,
-                        timestamp,
-                    });
-                }
+                let blocks = read_message_parts(&storage_root, &msg.id);
+                push_message(&mut messages, Message::new(role, blocks, timestamp));
             }
         }
 
+        let created = session.time.as_ref().map(|t| millis_to_datetime(t.created));
+        let timestamp = latest_timestamp.or(created).unwrap_or_else(Utc::now);
+        let (start_timestamp, active_duration) = session_times(&messages);
+        let title = session_title(session.title, &messages);
+
         Ok(Session {
             id: session.id,
             source: SessionSource::OpenCode,
             file_path: path.to_path_buf(),
             cwd: cwd.unwrap_or_else(|| ".".to_string()),
             git_branch: None, // OpenCode doesn't store git branch in session metadata
-            timestamp: latest_timestamp.unwrap_or_else(|| {
-                session
-                    .time
-                    .as_ref()
-                    .map(|t| millis_to_datetime(t.created))
-                    .unwrap_or_else(Utc::now)
-            }),
+            title,
+            parent_id: None,
+            is_subagent: false,
+            timestamp,
+            start_timestamp: start_timestamp.or(created).unwrap_or(timestamp),
+            active_duration,
             messages: join_consecutive_messages(messages),
         })
     }
@@ -187,13 +200,13 @@ fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
     Utc.timestamp_millis_opt(millis).single().unwrap_or_else(Utc::now)
 }
 
-/// Read all text parts for a message and concatenate them
-fn read_message_parts(storage_root: &Path, message_id: &str) -> String {
+/// Read all parts of a message as content blocks
+fn read_message_parts(storage_root: &Path, message_id: &str) -> Vec<ContentBlock> {
     let parts_dir = storage_root.join("part").join(message_id);
-    let mut texts: Vec<String> = Vec::new();
+    let mut blocks: Vec<ContentBlock> = Vec::new();
 
     if !parts_dir.exists() {
-        return String::new();
+        return blocks;
     }
 
     // Read all part files
@@ -221,19 +234,43 @@ fn read_message_parts(storage_root: &Path, message_id: &str) -> String {
     // Sort by filename to maintain order (prt_* IDs are sortable)
     part_entries.sort_by(|a, b| a.0.cmp(&b.0));
 
-    // Extract text from text parts only
     for (_filename, part) in part_entries {
-        if part.part_type == "text" {
-            if let Some(text) = part.text {
-                if !text.is_empty() {
-                    texts.push(text);
+        let text = part.text.filter(|t| !t.trim().is_empty());
+        match part.part_type.as_str() {
+            "text" => blocks.extend(text.map(|text| ContentBlock::Text { text })),
+            "reasoning" => blocks.extend(text.map(|text| ContentBlock::Reasoning { text })),
+            "tool" => {
+                let state = part.state;
+                blocks.push(ContentBlock::ToolUse {
+                    name: part.tool.unwrap_or_default(),
+                    input: state.as_ref().and_then(|s| s.input.clone()).unwrap_or_default(),
+                });
+                if let Some(state) = state {
+                    if let Some(output) = state.output {
+                        blocks.push(ContentBlock::ToolResult {
+                            output,
+                            is_error: state.status.as_deref() == Some("error"),
+                        });
+                    }
                 }
             }
+            "file" => {
+                let path = part.filename.or(part.url).unwrap_or_default();
+                if part.mime.as_deref().is_some_and(|m| m.starts_with("image/")) {
+                    blocks.push(ContentBlock::Image {
+                        media_type: part.mime,
+                        source: Some(path).filter(|p| !p.is_empty() && !p.starts_with("data:")),
+                    });
+                } else {
+                    blocks.push(ContentBlock::Attachment { path });
+                }
+            }
+            // Skip step-start, step-finish and other bookkeeping parts
+            _ => {}
         }
-        // Skip step-start, step-finish, tool parts (per user preference)
     }
 
-    texts.join("\n")
+    blocks
 }
 
 #[cfg(test)]
@@ -259,6 +296,36 @@ mod tests {
         assert!(dt.timestamp_millis() == 1763499168814);
     }
 
+    #[test]
+    fn test_read_message_parts_separates_reasoning() {
+        let dir = tempfile::tempdir().unwrap();
+        let parts = dir.path().join("part").join("msg_1");
+        std::fs::create_dir_all(&parts).unwrap();
+        std::fs::write(parts.join("prt_1.json"), r#"{"id":"prt_1","type":"reasoning","text":"Look at main.rs"}"#).unwrap();
+        std::fs::write(parts.join("prt_2.json"), r#"{"id":"prt_2","type":"text","text":"Found it"}"#).unwrap();
+        std::fs::write(parts.join("prt_3.json"), r#"{"id":"prt_3","type":"tool"}"#).unwrap();
+
+        let blocks = read_message_parts(dir.path(), "msg_1");
+        let message = Message::new(Role::Assistant, blocks, Utc::now());
+        assert_eq!(message.content, "Found it");
+        assert_eq!(message.reasoning.as_deref(), Some("Look at main.rs"));
+    }
+
+    #[test]
+    fn test_read_message_parts_keeps_tool_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let parts = dir.path().join("part").join("msg_1");
+        std::fs::create_dir_all(&parts).unwrap();
+        std::fs::write(parts.join("prt_1.json"), r#"{"id":"prt_1","type":"text","text":"Found it"}"#).unwrap();
+        std::fs::write(parts.join("prt_2.json"), r#"{"id":"prt_2","type":"tool","tool":"read","state":{"status":"completed","input":{"filePath":"main.rs"},"output":"fn main() {}"}}"#).unwrap();
+        std::fs::write(parts.join("prt_3.json"), r#"{"id":"prt_3","type":"step-finish"}"#).unwrap();
+
+        let blocks = read_message_parts(dir.path(), "msg_1");
+        let message = Message::new(Role::Assistant, blocks, Utc::now());
+        assert_eq!(message.tool_names(), vec!["read"]);
+        assert_eq!(message.blocks.len(), 3);
+    }
+
     #[test]
     fn test_get_storage_root() {
         let path = Path::new("/home/user/.local/share/opencode/storage/session/proj/ses_123.json");
@@ -0,0 +1,39 @@
+//! Where the user's home directory is, and where recall keeps its own files.
+//! `RECALL_HOME_OVERRIDE` (used by tests) stands in for the home directory, with
+//! recall's files under its `.config`, `.cache` and `.local/share`.
+
+use std::path::PathBuf;
+
+const HOME_OVERRIDE_ENV: &str = "RECALL_HOME_OVERRIDE";
+
+/// Get the user's home directory
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os(HOME_OVERRIDE_ENV)
+        .map(PathBuf::from)
+        .or_else(dirs::home_dir)
+}
+
+/// Get the directory of the config file
+pub fn config_dir() -> PathBuf {
+    recall_dir(".config", dirs::config_dir)
+}
+
+/// Get the directory of recall's own data (imported sessions, remappings)
+pub fn data_dir() -> PathBuf {
+    recall_dir(".local/share", dirs::data_dir)
+}
+
+/// Get the directory of the index cache (rebuilt from the session files)
+pub fn cache_dir() -> PathBuf {
+    recall_dir(".cache", dirs::cache_dir)
+}
+
+/// `recall` under `base` of the overridden home, or else the platform's directory
+fn recall_dir(base: &str, platform_dir: fn() -> Option<PathBuf>) -> PathBuf {
+    match std::env::var_os(HOME_OVERRIDE_ENV) {
+        Some(home) => PathBuf::from(home).join(base).join("recall"),
+        None => platform_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("recall"),
+    }
+}
@@ -0,0 +1,226 @@
+//! Working directories of moved projects: finding where a session's directory went,
+//! and remembering it so sessions recorded under the old path resume in the new one
+
+use crate::git;
+use crate::session::Session;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of directories offered for a missing one
+const MAX_CANDIDATES: usize = 9;
+
+/// Number of trailing path components of a missing directory matched against
+/// project folders (so `/old/repo/crates/api` can still be found by `repo`)
+const MATCHED_COMPONENTS: usize = 3;
+
+/// Get the path of the remappings file
+pub fn remaps_path() -> PathBuf {
+    crate::paths::data_dir().join("remaps.json")
+}
+
+/// Directories that moved, from old path to new path. A remapping covers the
+/// directories below the old path too.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Remaps {
+    #[serde(flatten)]
+    dirs: BTreeMap<String, String>,
+}
+
+impl Remaps {
+    /// Load the remappings, or none if the file doesn't exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&remaps_path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).context("Failed to read remaps file")?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse remaps file {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&remaps_path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Remember that `old` is now `new`. When both end in the same components, the
+    /// remapping is made for the directory above them, so `/a/repo/sub -> /b/repo/sub`
+    /// also moves the rest of `/a/repo`.
+    pub fn record(&mut self, old: &str, new: &str) {
+        let (mut old, mut new) = (Path::new(old), Path::new(new));
+        while let (Some(old_parent), Some(new_parent)) = (old.parent(), new.parent()) {
+            let shared = |a: &Path, b: &Path| a.file_name().is_some() && a.file_name() == b.file_name();
+            if !shared(old, new) || !shared(old_parent, new_parent) {
+                break;
+            }
+            old = old_parent;
+            new = new_parent;
+        }
+        self.dirs.insert(
+            old.to_string_lossy().to_string(),
+            new.to_string_lossy().to_string(),
+        );
+    }
+
+    /// Where a directory is now, if it (or a directory above it) was remapped.
+    /// The most specific remapping wins; remappings of remapped paths are followed.
+    pub fn resolve(&self, cwd: &str) -> Option<String> {
+        let mut current = PathBuf::from(cwd);
+        let mut moved = false;
+        // Bounded, in case remappings form a cycle
+        for _ in 0..self.dirs.len() {
+            let Some((rest, new)) = self
+                .dirs
+                .iter()
+                .filter_map(|(old, new)| current.strip_prefix(old).ok().map(|rest| (rest, new)))
+                .min_by_key(|(rest, _)| rest.components().count())
+            else {
+                break;
+            };
+            current = if rest.as_os_str().is_empty() {
+                PathBuf::from(new)
+            } else {
+                Path::new(new).join(rest)
+            };
+            moved = true;
+        }
+        moved.then(|| current.to_string_lossy().to_string())
+    }
+}
+
+/// Point a session whose directory no longer exists at its remapped directory.
+/// Returns false if the directory is missing and there's no remapping for it.
+pub fn remap_session_cwd(session: &mut Session, remaps: &Remaps) -> bool {
+    if session.cwd.is_empty() || Path::new(&session.cwd).is_dir() {
+        return true;
+    }
+    match remaps.resolve(&session.cwd).filter(|cwd| Path::new(cwd).is_dir()) {
+        Some(cwd) => {
+            session.cwd = cwd;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Existing directories that may be where a missing directory went: project folders
+/// (the directories of indexed sessions) named like it, or like one of the
+/// directories above it, or whose git remote is a repository of that name.
+/// The closest matches come first.
+pub fn candidates<'a>(missing: &str, known_dirs: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let missing = Path::new(missing);
+    let ancestors: Vec<&Path> = missing
+        .ancestors()
+        .filter(|a| a.file_name().is_some())
+        .take(MATCHED_COMPONENTS)
+        .collect();
+
+    // (matched ancestor's depth, whether the part below it is missing, directory)
+    let mut found: Vec<(usize, bool, String)> = Vec::new();
+    for dir in known_dirs {
+        let path = Path::new(dir);
+        if path == missing || !path.is_dir() {
+            continue;
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string());
+        let repo = git::remote_url(path).and_then(|url| git::repo_name(&url));
+        for (depth, ancestor) in ancestors.iter().enumerate() {
+            let ancestor_name = ancestor.file_name().map(|n| n.to_string_lossy().to_string());
+            if ancestor_name != name && ancestor_name != repo {
+                continue;
+            }
+            // Keep the part below the matched directory, if it's still there
+            let below = missing.strip_prefix(ancestor).map(|rest| path.join(rest));
+            let (candidate, partial) = match below {
+                Ok(below) if depth > 0 && below.is_dir() => (below, false),
+                _ => (path.to_path_buf(), depth > 0),
+            };
+            found.push((depth, partial, candidate.to_string_lossy().to_string()));
+            break;
+        }
+    }
+
+    found.sort();
+    let mut candidates: Vec<String> = Vec::new();
+    for (_, _, dir) in found {
+        if !candidates.contains(&dir) {
+            candidates.push(dir);
+        }
+    }
+    candidates.truncate(MAX_CANDIDATES);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_resolve() {
+        let mut remaps = Remaps::default();
+        remaps.record("/old/work/repo/crates/api", "/new/src/repo/crates/api");
+        assert_eq!(remaps.dirs.get("/old/work/repo").map(|s| s.as_str()), Some("/new/src/repo"));
+        assert_eq!(remaps.resolve("/old/work/repo/docs").as_deref(), Some("/new/src/repo/docs"));
+        assert_eq!(remaps.resolve("/old/work/repo").as_deref(), Some("/new/src/repo"));
+        assert_eq!(remaps.resolve("/old/work/repository"), None);
+        assert_eq!(remaps.resolve("/old/work"), None);
+
+        // Renamed directories are remapped as they are
+        remaps.record("/old/work/api-v1", "/new/src/api");
+        assert_eq!(remaps.resolve("/old/work/api-v1/src").as_deref(), Some("/new/src/api/src"));
+
+        // Moving a moved project again is followed
+        remaps.record("/new/src/repo", "/final/repo");
+        assert_eq!(remaps.resolve("/old/work/repo/docs").as_deref(), Some("/final/repo/docs"));
+    }
+
+    #[test]
+    fn test_remaps_file_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recall/remaps.json");
+        assert_eq!(Remaps::load_from(&path).unwrap(), Remaps::default());
+
+        let mut remaps = Remaps::default();
+        remaps.record("/a", "/b");
+        remaps.save_to(&path).unwrap();
+        assert_eq!(Remaps::load_from(&path).unwrap(), remaps);
+    }
+
+    #[test]
+    fn test_candidates_by_name_and_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        // Moved and renamed, but with the same remote
+        let renamed = root.join("projects/billing-service");
+        std::fs::create_dir_all(renamed.join(".git")).unwrap();
+        std::fs::write(
+            renamed.join(".git/config"),
+            "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = git@github.com:acme/billing.git\n",
+        )
+        .unwrap();
+        // Moved with the same name, and the subdirectory still there
+        let moved = root.join("src/billing");
+        std::fs::create_dir_all(moved.join("api")).unwrap();
+        let unrelated = root.join("src/other");
+        std::fs::create_dir_all(&unrelated).unwrap();
+
+        let known = [renamed.to_str().unwrap(), moved.to_str().unwrap(), unrelated.to_str().unwrap()];
+        assert_eq!(
+            candidates("/gone/billing/api", known),
+            vec![moved.join("api").to_string_lossy().to_string(), renamed.to_string_lossy().to_string()]
+        );
+        assert!(candidates("/gone/nothing-like-it", known).is_empty());
+    }
+}
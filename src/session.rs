@@ -1,8 +1,12 @@
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use crate::config::ResumeConfig;
+use crate::git::Commit;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SessionSource {
     #[serde(rename = "claude")]
     ClaudeCode,
@@ -53,7 +57,7 @@ impl SessionSource {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
     User,
@@ -69,11 +73,133 @@ impl Role {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// What a message is, beyond who it's from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    /// A regular conversation turn
+    #[default]
+    Turn,
+    /// Summary of the conversation so far, written when the context was compacted
+    CompactionSummary,
+}
+
+impl MessageKind {
+    fn is_turn(&self) -> bool {
+        *self == MessageKind::Turn
+    }
+}
+
+/// A piece of a message, as written by the agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Reasoning {
+        text: String,
+    },
+    ToolUse {
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        output: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+    /// An image, by reference (inline image data isn't kept)
+    Image {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        media_type: Option<String>,
+        /// URL or path of the image, if it isn't inline
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source: Option<String>,
+    },
+    /// A file attached to the message
+    Attachment {
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: Role,
+    /// Plain-text view of the message (its text blocks)
     pub content: String,
     pub timestamp: DateTime<Utc>,
+    /// Thinking/reasoning text the agent produced before this message (its reasoning blocks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    #[serde(default, skip_serializing_if = "MessageKind::is_turn")]
+    pub kind: MessageKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<ContentBlock>,
+}
+
+impl Message {
+    /// Build a message from its blocks, deriving its text and reasoning views
+    pub fn new(role: Role, blocks: Vec<ContentBlock>, timestamp: DateTime<Utc>) -> Self {
+        let texts: Vec<&str> = blocks
+            .iter()
+            .filter_map(|b| match b {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        let thoughts: Vec<&str> = blocks
+            .iter()
+            .filter_map(|b| match b {
+                ContentBlock::Reasoning { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            role,
+            content: texts.join("\n"),
+            timestamp,
+            reasoning: (!thoughts.is_empty()).then(|| thoughts.join("\n\n")),
+            kind: MessageKind::Turn,
+            blocks,
+        }
+    }
+
+    /// Build a message with a single text block
+    pub fn text(role: Role, text: impl Into<String>, timestamp: DateTime<Utc>) -> Self {
+        Self::new(role, vec![ContentBlock::Text { text: text.into() }], timestamp)
+    }
+
+    pub fn with_kind(mut self, kind: MessageKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Whether this is something the user actually said
+    pub fn is_user_turn(&self) -> bool {
+        self.role == Role::User && self.kind == MessageKind::Turn && !self.only_tool_results()
+    }
+
+    /// Whether the message only carries tool results back to the agent
+    pub fn only_tool_results(&self) -> bool {
+        !self.blocks.is_empty()
+            && self
+                .blocks
+                .iter()
+                .all(|b| matches!(b, ContentBlock::ToolResult { .. }))
+    }
+
+    /// Names of the tools the message called
+    pub fn tool_names(&self) -> Vec<&str> {
+        self.blocks
+            .iter()
+            .filter_map(|b| match b {
+                ContentBlock::ToolUse { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -83,11 +209,79 @@ pub struct Session {
     pub file_path: PathBuf,
     pub cwd: String,
     pub git_branch: Option<String>,
+    /// Agent-provided title, or one derived from the first user prompt
+    pub title: String,
+    /// Session this one was continued or forked from (or the session that ran this subagent)
+    pub parent_id: Option<String>,
+    /// Whether this is a subagent (sidechain) transcript
+    pub is_subagent: bool,
+    /// Time of the last message
     pub timestamp: DateTime<Utc>,
+    /// Time of the first message
+    pub start_timestamp: DateTime<Utc>,
+    /// Time spent in the conversation, excluding idle gaps
+    pub active_duration: chrono::Duration,
     pub messages: Vec<Message>,
 }
 
 impl Session {
+    /// Number of conversation turns (compaction summaries and tool results sent back
+    /// on their own aren't turns)
+    pub fn turn_count(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|m| m.kind.is_turn() && !m.only_tool_results())
+            .count()
+    }
+
+    /// Drop reasoning from every message, and the messages that had nothing else
+    pub fn strip_reasoning(&mut self) {
+        for message in &mut self.messages {
+            message.reasoning = None;
+            message
+                .blocks
+                .retain(|b| !matches!(b, ContentBlock::Reasoning { .. }));
+        }
+        self.drop_empty_messages();
+    }
+
+    /// Drop tool calls and results from every message, and the messages that had
+    /// nothing else
+    pub fn strip_tools(&mut self) {
+        for message in &mut self.messages {
+            message.blocks.retain(|b| {
+                !matches!(b, ContentBlock::ToolUse { .. } | ContentBlock::ToolResult { .. })
+            });
+        }
+        self.drop_empty_messages();
+    }
+
+    /// Drop messages with no blocks left, joining the ones that end up next to a
+    /// message from the same role
+    fn drop_empty_messages(&mut self) {
+        self.messages.retain(|m| !m.blocks.is_empty());
+        self.messages = crate::parser::join_consecutive_messages(std::mem::take(&mut self.messages));
+    }
+
+    /// Messages containing any of a query's words (case-insensitively), as (message
+    /// index, number of matches), in conversation order
+    pub fn find_messages(&self, query: &str) -> Vec<(usize, usize)> {
+        let query_lower = query.to_lowercase();
+        let query_terms: Vec<&str> = query_lower.split_whitespace().collect();
+        self.messages
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, m)| {
+                let content_lower = m.content.to_lowercase();
+                let count: usize = query_terms
+                    .iter()
+                    .map(|t| content_lower.matches(t).count())
+                    .sum();
+                (count > 0).then_some((idx, count))
+            })
+            .collect()
+    }
+
     /// Get the project name from cwd (last path component)
     pub fn project_name(&self) -> &str {
         std::path::Path::new(&self.cwd)
@@ -96,30 +290,45 @@ impl Session {
             .unwrap_or(&self.cwd)
     }
 
-    /// Get the resume command for this session
-    /// Checks RECALL_CLAUDE_CMD / RECALL_CODEX_CMD / RECALL_FACTORY_CMD env vars first, falls back to defaults
-    /// Env var format: "program arg1 arg2 {id}" where {id} is replaced with session ID
-    pub fn resume_command(&self) -> (String, Vec<String>) {
+    /// Whether this is a read-only session imported from an export bundle
+    pub fn is_archived(&self) -> bool {
+        crate::archive::is_archived(&self.file_path)
+    }
+
+    /// Get the resume command for this session from the first template of: a
+    /// `[[resume.projects]]` entry matching its directory, the RECALL_CLAUDE_CMD /
+    /// RECALL_CODEX_CMD / RECALL_FACTORY_CMD / RECALL_OPENCODE_CMD env var, or the
+    /// `[resume]` section; falls back to the tool's own command.
+    ///
+    /// Templates are split shell-style ("program arg 'quoted arg' {id}"), then
+    /// {id}, {cwd}, {branch}, {file}, {source} and {title} are replaced in each word.
+    /// Fails when the env var doesn't split (config templates are checked on load).
+    pub fn resume_command(&self, config: &ResumeConfig) -> Result<(String, Vec<String>)> {
         let env_var = match self.source {
             SessionSource::ClaudeCode => "RECALL_CLAUDE_CMD",
             SessionSource::CodexCli => "RECALL_CODEX_CMD",
             SessionSource::Factory => "RECALL_FACTORY_CMD",
             SessionSource::OpenCode => "RECALL_OPENCODE_CMD",
         };
+        let source = self.source.as_str();
+        let words = match (config.project_template(source, &self.cwd), std::env::var(env_var)) {
+            (Some(template), _) => Some(crate::shell::split(template)?),
+            (None, Ok(template)) => Some(
+                crate::shell::split(&template)
+                    .with_context(|| format!("Invalid resume command in {}", env_var))?,
+            ),
+            (None, Err(_)) => config.template(source).map(crate::shell::split).transpose()?,
+        };
 
-        if let Ok(cmd) = std::env::var(env_var) {
-            let cmd = cmd.replace("{id}", &self.id);
-            let parts: Vec<&str> = cmd.split_whitespace().collect();
-            if !parts.is_empty() {
-                return (
-                    parts[0].to_string(),
-                    parts[1..].iter().map(|s| s.to_string()).collect(),
-                );
+        if let Some(words) = words {
+            let mut words = words.iter().map(|w| self.fill_placeholders(w));
+            if let Some(program) = words.next() {
+                return Ok((program, words.collect()));
             }
         }
 
         // Default commands
-        match self.source {
+        Ok(match self.source {
             SessionSource::ClaudeCode => (
                 "claude".to_string(),
                 vec!["--resume".to_string(), self.id.clone()],
@@ -136,14 +345,77 @@ impl Session {
                 "opencode".to_string(),
                 vec!["run".to_string(), "-s".to_string(), self.id.clone()],
             ),
+        })
+    }
+
+    /// The resume command as a shell command line
+    pub fn resume_command_line(&self, config: &ResumeConfig) -> Result<String> {
+        let (cmd, args) = self.resume_command(config)?;
+        Ok(crate::shell::join(&std::iter::once(cmd).chain(args).collect::<Vec<_>>()))
+    }
+
+    /// Replace the placeholders in a template word, in one pass (so braces in the
+    /// substituted values are kept as they are)
+    fn fill_placeholders(&self, word: &str) -> String {
+        let mut filled = String::with_capacity(word.len());
+        let mut rest = word;
+        while let Some(start) = rest.find('{') {
+            filled.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let placeholder = rest
+                .find('}')
+                .and_then(|end| Some((end, self.placeholder(&rest[1..end])?)));
+            match placeholder {
+                Some((end, value)) => {
+                    filled.push_str(&value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    filled.push('{');
+                    rest = &rest[1..];
+                }
+            }
         }
+        filled.push_str(rest);
+        filled
     }
+
+    /// Value of a template placeholder (its name without the braces)
+    fn placeholder(&self, name: &str) -> Option<Cow<'_, str>> {
+        Some(match name {
+            "id" => Cow::Borrowed(self.id.as_str()),
+            "cwd" => Cow::Borrowed(self.cwd.as_str()),
+            "branch" => Cow::Borrowed(self.git_branch.as_deref().unwrap_or("")),
+            "file" => self.file_path.to_string_lossy(),
+            "source" => Cow::Borrowed(self.source.as_str()),
+            "title" => Cow::Borrowed(self.title.as_str()),
+            _ => return None,
+        })
+    }
+}
+
+/// Components of a search result's ranking score (for `--explain`)
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ScoreBreakdown {
+    /// BM25 relevance of the best-matching message (including phrase boost)
+    pub relevance: f32,
+    /// Bonus for the matched message's position within the session
+    pub position_bonus: f32,
+    /// Age of the session's last activity, in days
+    pub age_days: f64,
+    /// Recency multiplier derived from the session's age
+    pub recency_multiplier: f64,
+    /// Final ranking score: (relevance + position_bonus) * recency_multiplier
+    pub final_score: f64,
 }
 
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub session: Session,
+    /// Session-level score (relevance of the best message plus its position bonus)
     pub score: f32,
+    /// How the final ranking score was computed
+    pub score_breakdown: ScoreBreakdown,
     /// Index of the most recent message containing a match
     pub matched_message_index: usize,
     /// Snippet from the matched message (newlines replaced with spaces)
@@ -163,79 +435,318 @@ pub struct SearchResult {
 pub struct SearchOutput {
     pub query: String,
     pub results: Vec<SearchResultOutput>,
+    /// Pass as `--page-token` to get the next page (absent on the last page)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
 }
 
 /// Single search result in JSON output
 #[derive(Debug, Serialize)]
 pub struct SearchResultOutput {
     pub session_id: String,
+    pub title: String,
     pub source: SessionSource,
     pub cwd: String,
     pub timestamp: DateTime<Utc>,
+    pub start_timestamp: DateTime<Utc>,
+    pub active_duration_secs: i64,
+    /// Session this one was continued or forked from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Whether this is a subagent transcript
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_subagent: bool,
     pub relevant_messages: Vec<Message>,
     pub resume_command: String,
+    /// Score components (only with `--explain`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ScoreBreakdown>,
 }
 
 /// Output format for `recall list`
 #[derive(Debug, Serialize)]
 pub struct ListOutput {
     pub sessions: Vec<SessionSummary>,
+    /// Pass as `--page-token` to get the next page (absent on the last page)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
 }
 
 /// Session summary for list output (no messages)
 #[derive(Debug, Serialize)]
 pub struct SessionSummary {
     pub session_id: String,
+    pub title: String,
     pub source: SessionSource,
     pub cwd: String,
     pub timestamp: DateTime<Utc>,
+    pub start_timestamp: DateTime<Utc>,
+    pub active_duration_secs: i64,
+    /// Session this one was continued or forked from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Whether this is a subagent transcript
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_subagent: bool,
+    /// Whether this is a read-only session imported with `recall import`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
     pub resume_command: String,
 }
 
-/// Output format for `recall read`
-#[derive(Debug, Serialize)]
+/// Output format for `recall read` (also the JSON transcript format of export bundles)
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ReadOutput {
     pub session_id: String,
+    pub title: String,
     pub source: SessionSource,
     pub cwd: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
     pub timestamp: DateTime<Utc>,
+    pub start_timestamp: DateTime<Utc>,
+    pub active_duration_secs: i64,
+    /// Session this one was continued or forked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Whether this is a subagent transcript
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_subagent: bool,
+    /// Whether this is a read-only session imported with `recall import`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
     pub messages: Vec<Message>,
+    /// Commits made during the session (with `--commits`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commits: Option<Vec<Commit>>,
+    #[serde(default)]
+    pub resume_command: String,
+}
+
+/// Output format for `recall read --thread`
+#[derive(Debug, Serialize)]
+pub struct ThreadOutput {
+    pub session_id: String,
+    /// Sessions in the thread, oldest first
+    pub sessions: Vec<SessionSummary>,
+    /// Messages from all sessions, with those carried over into continuations deduplicated
+    pub messages: Vec<ThreadMessage>,
+}
+
+/// Message in a stitched thread, tagged with the session it came from
+#[derive(Debug, Serialize)]
+pub struct ThreadMessage {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub message: Message,
+}
+
+/// Output format for `recall handoff`
+#[derive(Debug, Serialize)]
+pub struct HandoffOutput {
+    /// ID of the new session in the target tool
+    pub session_id: String,
+    pub source: SessionSource,
+    pub file_path: PathBuf,
+    /// Directory to resume the session in
+    pub cwd: String,
+    /// Session that was handed off
+    pub from_session_id: String,
+    pub resume_command: String,
+}
+
+/// Output format for `recall blame`
+#[derive(Debug, Serialize)]
+pub struct BlameOutput {
+    pub commit: Commit,
+    /// Sessions that were active when the commit was made, most likely first
+    pub sessions: Vec<SessionSummary>,
+}
+
+/// Output format for `recall related`
+#[derive(Debug, Serialize)]
+pub struct RelatedOutput {
+    pub session_id: String,
+    pub results: Vec<RelatedResultOutput>,
+}
+
+/// Single related session in JSON output
+#[derive(Debug, Serialize)]
+pub struct RelatedResultOutput {
+    pub session_id: String,
+    pub title: String,
+    pub source: SessionSource,
+    pub cwd: String,
+    pub timestamp: DateTime<Utc>,
+    pub start_timestamp: DateTime<Utc>,
+    pub active_duration_secs: i64,
+    pub score: f32,
+    pub snippet: String,
     pub resume_command: String,
 }
 
 impl Session {
     /// Convert to ReadOutput for JSON serialization
-    pub fn to_read_output(&self) -> ReadOutput {
-        let (cmd, args) = self.resume_command();
-        let resume_str = std::iter::once(cmd)
-            .chain(args)
-            .collect::<Vec<_>>()
-            .join(" ");
-
-        ReadOutput {
+    pub fn to_read_output(&self, resume: &ResumeConfig) -> Result<ReadOutput> {
+        Ok(ReadOutput {
             session_id: self.id.clone(),
+            title: self.title.clone(),
             source: self.source,
             cwd: self.cwd.clone(),
+            git_branch: self.git_branch.clone(),
             timestamp: self.timestamp,
+            start_timestamp: self.start_timestamp,
+            active_duration_secs: self.active_duration.num_seconds(),
+            parent_id: self.parent_id.clone(),
+            is_subagent: self.is_subagent,
+            archived: self.is_archived(),
             messages: self.messages.clone(),
-            resume_command: resume_str,
-        }
+            commits: None,
+            resume_command: self.resume_command_line(resume)?,
+        })
     }
 
     /// Convert to SessionSummary for list output
-    pub fn to_summary(&self) -> SessionSummary {
-        let (cmd, args) = self.resume_command();
-        let resume_str = std::iter::once(cmd)
-            .chain(args)
-            .collect::<Vec<_>>()
-            .join(" ");
-
-        SessionSummary {
+    pub fn to_summary(&self, resume: &ResumeConfig) -> Result<SessionSummary> {
+        Ok(SessionSummary {
             session_id: self.id.clone(),
+            title: self.title.clone(),
             source: self.source,
             cwd: self.cwd.clone(),
             timestamp: self.timestamp,
-            resume_command: resume_str,
+            start_timestamp: self.start_timestamp,
+            active_duration_secs: self.active_duration.num_seconds(),
+            parent_id: self.parent_id.clone(),
+            is_subagent: self.is_subagent,
+            archived: self.is_archived(),
+            resume_command: self.resume_command_line(resume)?,
+        })
+    }
+}
+
+#[cfg(test)]
+impl Session {
+    /// A Claude Code session with no messages, for tests to fill in with the `with_*`
+    /// methods
+    pub fn test(id: &str) -> Self {
+        Session {
+            id: id.to_string(),
+            source: SessionSource::ClaudeCode,
+            file_path: PathBuf::from(format!("{}.jsonl", id)),
+            cwd: String::new(),
+            git_branch: None,
+            title: String::new(),
+            parent_id: None,
+            is_subagent: false,
+            timestamp: Default::default(),
+            start_timestamp: Default::default(),
+            active_duration: chrono::Duration::zero(),
+            messages: Vec::new(),
         }
     }
+
+    pub fn with_source(mut self, source: SessionSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_file_path(mut self, file_path: impl Into<PathBuf>) -> Self {
+        self.file_path = file_path.into();
+        self
+    }
+
+    pub fn with_cwd(mut self, cwd: impl Into<String>) -> Self {
+        self.cwd = cwd.into();
+        self
+    }
+
+    pub fn with_branch(mut self, branch: &str) -> Self {
+        self.git_branch = Some(branch.to_string());
+        self
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Link to the session it was continued or forked from, or (with `is_subagent`)
+    /// the session that ran it
+    pub fn with_parent(mut self, parent_id: &str, is_subagent: bool) -> Self {
+        self.parent_id = Some(parent_id.to_string());
+        self.is_subagent = is_subagent;
+        self
+    }
+
+    /// Set when the session started and last had activity, and how long it was active
+    pub fn with_times(
+        mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        active: chrono::Duration,
+    ) -> Self {
+        self.start_timestamp = start;
+        self.timestamp = end;
+        self.active_duration = active;
+        self
+    }
+
+    pub fn with_messages(mut self, messages: Vec<Message>) -> Self {
+        self.messages = messages;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProjectResumeConfig;
+
+    #[test]
+    fn test_resume_templates() {
+        let session = Session::test("abc")
+            .with_file_path("/home/me/.claude/projects/x/abc.jsonl")
+            .with_cwd("/work/My Project")
+            .with_branch("main")
+            .with_title("Fix it {id}");
+        let config = ResumeConfig {
+            projects: vec![ProjectResumeConfig {
+                cwd: "/work/My Project".to_string(),
+                commands: [(
+                    "claude".to_string(),
+                    "wrap --dir {cwd} --name '{source}: {title}' -- claude -r {id} --branch={branch}"
+                        .to_string(),
+                )]
+                .into(),
+            }],
+            ..Default::default()
+        };
+
+        let (program, args) = session.resume_command(&config).unwrap();
+        assert_eq!(program, "wrap");
+        // Placeholders in the title aren't filled in
+        assert_eq!(
+            args,
+            vec!["--dir", "/work/My Project", "--name", "claude: Fix it {id}", "--", "claude", "-r", "abc", "--branch=main"]
+        );
+    }
+
+    #[test]
+    fn test_strip_tools_joins_what_is_left() {
+        let now = Utc::now();
+        let tool_use = ContentBlock::ToolUse { name: "Bash".to_string(), input: serde_json::json!({}) };
+        let tool_result = ContentBlock::ToolResult { output: "ok".to_string(), is_error: false };
+        let mut session = Session::test("abc").with_messages(vec![
+            Message::text(Role::User, "run it", now),
+            Message::new(Role::Assistant, vec![tool_use, tool_result.clone()], now),
+            Message::text(Role::User, "and again", now),
+            Message::new(Role::User, vec![tool_result], now),
+        ]);
+        assert_eq!(session.turn_count(), 3);
+
+        session.strip_tools();
+        assert_eq!(session.messages.len(), 1);
+        assert_eq!(session.messages[0].content, "run it\n\nand again");
+        assert_eq!(session.turn_count(), 1);
+    }
 }
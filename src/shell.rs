@@ -0,0 +1,113 @@
+//! Shell-style splitting and quoting of command lines (for command templates)
+
+use anyhow::Result;
+use std::borrow::Cow;
+
+/// Split a command line into words like a POSIX shell: whitespace separates words,
+/// single quotes keep text literally, double quotes allow `\"`, `\\` and `\$`
+/// escapes, and a backslash outside quotes escapes the next character
+pub fn split(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Whether a word is in progress (so `''` still makes an empty word)
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => anyhow::bail!("Unclosed single quote in {:?}", line),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => anyhow::bail!("Unclosed double quote in {:?}", line),
+                        },
+                        Some(c) => word.push(c),
+                        None => anyhow::bail!("Unclosed double quote in {:?}", line),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => word.push(c),
+                    None => anyhow::bail!("Trailing backslash in {:?}", line),
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Quote a word for a POSIX shell, if it needs quoting
+pub fn quote(word: &str) -> Cow<'_, str> {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(format!("'{}'", word.replace('\'', r"'\''")))
+    }
+}
+
+/// Join words into a command line that a shell splits back into the same words
+pub fn join<S: AsRef<str>>(words: &[S]) -> String {
+    words
+        .iter()
+        .map(|w| quote(w.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("claude  --resume {id}").unwrap(), vec!["claude", "--resume", "{id}"]);
+        assert_eq!(
+            split(r#"sh -c 'cd "{cwd}" && exec x' "a b" c\ d "q\"t" ''"#).unwrap(),
+            vec!["sh", "-c", r#"cd "{cwd}" && exec x"#, "a b", "c d", "q\"t", ""]
+        );
+        assert!(split("'open").is_err());
+        assert!(split("\"open").is_err());
+        assert!(split("end\\").is_err());
+    }
+
+    #[test]
+    fn test_quote_round_trips() {
+        let words = ["codex", "resume", "a b", "it's", "", "/tmp/x.jsonl"];
+        let line = join(&words);
+        assert_eq!(line, r#"codex resume 'a b' 'it'\''s' '' /tmp/x.jsonl"#);
+        assert_eq!(split(&line).unwrap(), words);
+    }
+}
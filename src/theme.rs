@@ -54,6 +54,18 @@ pub struct Theme {
     pub scope_label_fg: Color,
     /// Focus indicator color (left border on focused message in preview)
     pub focus_indicator: Color,
+    /// Markdown headings in the preview
+    pub heading_fg: Color,
+    /// Inline code and code blocks in the preview
+    pub code_fg: Color,
+    /// Keywords in code blocks
+    pub code_keyword_fg: Color,
+    /// String literals in code blocks
+    pub code_string_fg: Color,
+    /// Number literals in code blocks
+    pub code_number_fg: Color,
+    /// Comments in code blocks
+    pub code_comment_fg: Color,
 }
 
 impl Theme {
@@ -94,6 +106,12 @@ impl Theme {
             separator_fg: Color::Rgb(60, 60, 65),     // subtle separator
             scope_label_fg: Color::Rgb(140, 140, 140), // readable but not bright
             focus_indicator: Color::Rgb(100, 180, 255), // bright blue
+            heading_fg: Color::Rgb(230, 230, 235),
+            code_fg: Color::Rgb(200, 200, 190),
+            code_keyword_fg: Color::Rgb(200, 140, 230), // soft purple
+            code_string_fg: Color::Rgb(150, 200, 120),  // soft green
+            code_number_fg: Color::Rgb(230, 170, 100),  // soft orange
+            code_comment_fg: Color::Rgb(110, 115, 125),
         }
     }
 
@@ -125,6 +143,12 @@ impl Theme {
             separator_fg: Color::Rgb(195, 195, 200),  // visible on light bg
             scope_label_fg: Color::Rgb(100, 100, 100), // readable on light bg
             focus_indicator: Color::Rgb(50, 120, 200), // darker blue for light bg
+            heading_fg: Color::Rgb(20, 20, 25),
+            code_fg: Color::Rgb(60, 60, 70),
+            code_keyword_fg: Color::Rgb(140, 50, 170), // purple (darker for light bg)
+            code_string_fg: Color::Rgb(50, 120, 30),   // green (darker for light bg)
+            code_number_fg: Color::Rgb(170, 90, 0),    // orange (darker for light bg)
+            code_comment_fg: Color::Rgb(130, 130, 140),
         }
     }
 }
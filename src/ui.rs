@@ -1,5 +1,8 @@
 use crate::app::{App, SearchScope};
-use crate::session::{Role, SessionSource};
+use crate::git::Commit;
+use crate::index::SubagentFilter;
+use crate::markdown::{self, SpanKind, StyledLine};
+use crate::session::{MessageKind, Role, Session, SessionSource};
 use crate::theme::Theme;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -109,7 +112,17 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
     let search_width = (area.width as usize).saturating_sub(scope_width + 1); // +1 for left margin before widget
 
     // Build middle line with search on left, scope widget on right
-    let middle_line = if app.query.is_empty() {
+    let middle_line = if let Some(ref session) = app.related_to {
+        let label = format!(" Related to 📁 {}", session.project_name());
+        let padding = search_width.saturating_sub(label.chars().count() + 1);
+        let mut spans = vec![
+            Span::styled(label, Style::default().fg(t.accent)),
+            Span::raw(" ".repeat(padding)), // fill to push scope right
+            Span::styled(" ", Style::default()), // margin before widget
+        ];
+        spans.extend(scope_widget.clone());
+        Line::from(spans)
+    } else if app.query.is_empty() {
         let placeholder = " Search...";
         let padding = search_width.saturating_sub(placeholder.len());
         let mut spans = vec![
@@ -174,14 +187,20 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
     let available_width = area.width.saturating_sub(2) as usize;
 
     if app.results.is_empty() {
-        // Show hint to search everywhere if scoped and no results
+        // Show hint to search more widely if scoped and no results
         let is_scoped = !matches!(app.search_scope, SearchScope::Everything);
-        if is_scoped {
+        if app.related_to.is_some() {
+            let paragraph = Paragraph::new(Span::styled(
+                " No related sessions.",
+                Style::default().fg(t.snippet_fg),
+            ));
+            frame.render_widget(paragraph, area);
+        } else if is_scoped {
             let prefix = if app.query.is_empty() { "Nothing here." } else { "No results." };
             let hint = Line::from(vec![
                 Span::styled(format!(" {} Press ", prefix), Style::default().fg(t.snippet_fg)),
                 Span::styled(" / ", Style::default().bg(t.keycap_bg)),
-                Span::styled(" to search everywhere.", Style::default().fg(t.snippet_fg)),
+                Span::styled(" to search more widely.", Style::default().fg(t.snippet_fg)),
             ]);
             frame.render_widget(Paragraph::new(hint), area);
         } else if !app.query.is_empty() {
@@ -201,8 +220,21 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
         .map(|(i, result)| {
             let is_selected = i == app.selected;
 
-            // Format time ago
-            let time_ago = format_time_ago(result.session.timestamp);
+            // Format time ago, plus how long the conversation ran
+            let mut time_ago = format_time_ago(result.session.timestamp);
+            if let Some(duration) = format_duration(result.session.active_duration) {
+                time_ago.push_str(&format!(" · {}", duration));
+            }
+            if result.session.is_subagent {
+                time_ago.push_str(" · subagent");
+            }
+            if app.is_archived(&result.session) {
+                time_ago.push_str(" · archived");
+            }
+            // Number of sessions in the thread (when grouping by thread)
+            if let Some(&size) = app.thread_sizes.get(&result.session.id).filter(|&&n| n > 1) {
+                time_ago.push_str(&format!(" · 🧵 {}", size));
+            }
 
             // Source-specific color
             let source_color = match result.session.source {
@@ -219,16 +251,35 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 Style::default()
             };
 
-            let header_spans = vec![
+            let source_label = format!(
+                "{} {}",
+                result.session.source.icon(),
+                result.session.source.display_name()
+            );
+            let time_label = format!("  {}", time_ago);
+
+            // Title fills whatever width the rest of the header leaves
+            let used = 3
+                + result.session.project_name().chars().count()
+                + 2
+                + source_label.chars().count()
+                + time_label.chars().count();
+            let title = truncate_chars(&result.session.title, available_width.saturating_sub(used + 2));
+
+            let mut header_spans = vec![
                 Span::styled("📁 ", header_style),
                 Span::styled(result.session.project_name(), header_style),
                 Span::styled("  ", header_style),
-                Span::styled(
-                    format!("{} {}", result.session.source.icon(), result.session.source.display_name()),
-                    Style::default().fg(source_color),
-                ),
-                Span::styled(format!("  {}", time_ago), header_style),
             ];
+            if !title.is_empty() {
+                header_spans.push(Span::styled(
+                    title,
+                    header_style.add_modifier(Modifier::BOLD),
+                ));
+                header_spans.push(Span::styled("  ", header_style));
+            }
+            header_spans.push(Span::styled(source_label, Style::default().fg(source_color)));
+            header_spans.push(Span::styled(time_label, header_style));
 
             // Truncate snippet to fit available width (Tantivy already centered it)
             let snippet: String = result.snippet.chars().take(available_width).collect();
@@ -313,7 +364,8 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
     // Extract values we need before mutating app
     let file_path = result.session.file_path.clone();
     let matched_message_index = result.matched_message_index;
-    let match_fragment = result.match_fragment.clone();
+    // The fragment is raw content; match it against rendered lines without markup
+    let match_fragment = markdown::plain_text(&result.match_fragment);
 
     // Load the full session for preview
     let session = match crate::parser::parse_session_file(&file_path) {
@@ -330,6 +382,17 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
     // Determine focused message (default to matched message)
     let focused_idx = app.focused_message.unwrap_or(matched_message_index);
 
+    // Highlight the find query while finding, else the search query
+    let highlight_query = match &app.find {
+        Some(find) => find.query.clone(),
+        None => app.query.clone(),
+    };
+    // Line of the focused message with the current find match (scrolled to instead of
+    // its start), and the matches in the lines of that message before it
+    let mut focused_match_line: Option<usize> = None;
+    let target_match = app.find.as_ref().and_then(|f| f.current_match()).map_or(0, |(_, n)| n);
+    let mut focused_matches_seen = 0;
+
     // Build preview lines with chat bubble style
     let mut lines: Vec<Line> = Vec::new();
     // Reserve chars for: focus indicator (1-2) + bubble padding (2 left/right)
@@ -340,6 +403,13 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
     // Track line index where each message starts (for scrolling)
     let mut message_start_lines: Vec<usize> = Vec::new();
 
+    // Subagents are nested after the message they were started from, and commits
+    // after the message they were made after
+    let subagents = app.subagents.clone();
+    let mut next_subagent = 0;
+    let commits = app.commits.clone();
+    let mut next_commit = 0;
+
     for (i, message) in session.messages.iter().enumerate() {
         // Track where this message starts
         message_start_lines.push(lines.len());
@@ -366,6 +436,31 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
             lines.push(Line::from(""));
         }
 
+        let next_message_time = session.messages.get(i + 1).map(|m| m.timestamp);
+
+        // Compaction summaries divide the conversation (summary text shown when expanded)
+        if message.kind == MessageKind::CompactionSummary {
+            for (line_idx, display_line) in compaction_lines(&message.content, is_expanded, bubble_width)
+                .into_iter()
+                .enumerate()
+            {
+                let prefix = if is_focused { focus_prefix.clone() } else { unfocused_prefix.clone() };
+                let style = if line_idx == 0 {
+                    Style::default().fg(t.dim_fg).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(t.dim_fg)
+                };
+                lines.push(Line::from(vec![prefix, Span::styled(format!(" {}", display_line), style)]));
+            }
+            if is_focused {
+                app.focused_message_expandable = true;
+            }
+            message_line_ranges.push((message_start_lines[i], lines.len()));
+            nest_subagents(&mut lines, &subagents, &mut next_subagent, next_message_time, bubble_width);
+            nest_commits(&mut lines, &commits, &mut next_commit, next_message_time, bubble_width);
+            continue;
+        }
+
         // Role label
         let role_label = match message.role {
             Role::User => "You",
@@ -392,8 +487,54 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
             ),
         ]));
 
-        // Message content with word wrapping
-        let wrapped_lines = wrap_text(&message.content, bubble_width);
+        // Reasoning (dimmed, collapsed to a header line until the message is expanded)
+        let reasoning = message
+            .reasoning
+            .as_deref()
+            .map(|r| reasoning_lines(r, is_expanded, bubble_width))
+            .unwrap_or_default();
+        for display_line in &reasoning {
+            let prefix = if is_focused { focus_prefix.clone() } else { unfocused_prefix.clone() };
+            lines.push(Line::from(vec![
+                prefix,
+                Span::styled(
+                    format!(" {:<width$}", display_line, width = bubble_width + 1),
+                    Style::default()
+                        .fg(t.dim_fg)
+                        .bg(msg_bg)
+                        .add_modifier(Modifier::ITALIC),
+                ),
+            ]));
+        }
+
+        // Turns with no text (only tool calls or results) show which tools they called,
+        // or how many results they sent back
+        let tools = message.tool_names();
+        let tool_label = if !tools.is_empty() {
+            Some(format!("⚙ {}", tools.join(", ")))
+        } else if message.only_tool_results() {
+            Some(match message.blocks.len() {
+                1 => "⚙ tool result".to_string(),
+                n => format!("⚙ {} tool results", n),
+            })
+        } else {
+            None
+        };
+        if let Some(label) = tool_label.filter(|_| message.content.is_empty()) {
+            let prefix = if is_focused { focus_prefix.clone() } else { unfocused_prefix.clone() };
+            let label = truncate_chars(&label, bubble_width);
+            lines.push(Line::from(vec![
+                prefix,
+                Span::styled(
+                    format!(" {:<width$}", label, width = bubble_width + 1),
+                    Style::default().fg(t.dim_fg).bg(msg_bg),
+                ),
+            ]));
+        }
+
+        // Message content, rendered as markdown
+        let rendered = markdown::render(&message.content, bubble_width);
+        let wrapped_lines: Vec<String> = rendered.iter().map(|l| l.text()).collect();
         let is_matched = i == matched_message_index;
         let max_lines = if is_expanded { usize::MAX } else { 12 };
 
@@ -419,7 +560,8 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
 
         // Track if focused message can be expanded/collapsed
         if is_focused {
-            app.focused_message_expandable = wrapped_lines.len() > 12 || is_expanded;
+            app.focused_message_expandable =
+                wrapped_lines.len() > 12 || is_expanded || message.reasoning.is_some();
         }
 
         for (line_idx, display_line) in &lines_to_show {
@@ -448,8 +590,14 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
             ];
 
             if !display_line.is_empty() {
-                let highlighted = highlight_matches_owned(display_line, &app.query);
-                for span in highlighted {
+                let matches = match_ranges(display_line, &highlight_query);
+                if is_focused && !matches.is_empty() {
+                    if focused_match_line.is_none() || focused_matches_seen <= target_match {
+                        focused_match_line = Some(lines.len());
+                    }
+                    focused_matches_seen += matches.len();
+                }
+                for span in markdown_spans(&rendered[*line_idx], &matches) {
                     spans.push(Span::styled(span.content, span.style.bg(msg_bg)));
                 }
             }
@@ -460,6 +608,9 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
 
         // Record the line range for this message
         message_line_ranges.push((message_start_lines[i], lines.len()));
+
+        nest_subagents(&mut lines, &subagents, &mut next_subagent, next_message_time, bubble_width);
+        nest_commits(&mut lines, &commits, &mut next_commit, next_message_time, bubble_width);
     }
 
     // Store message line ranges for mouse click detection
@@ -472,7 +623,8 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // Auto-scroll to focused message when pending (triggered by selection change or navigation)
     if app.pending_auto_scroll {
-        if let Some(&start_line) = message_start_lines.get(focused_idx) {
+        let find_line = focused_match_line.filter(|_| app.find.is_some());
+        if let Some(start_line) = find_line.or(message_start_lines.get(focused_idx).copied()) {
             // Scroll to show focused message with some context above
             app.preview_scroll = start_line.saturating_sub(2).min(max_scroll);
         }
@@ -492,13 +644,108 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+/// Add lines for the subagents that started before the next message (or all remaining
+/// ones after the last message)
+fn nest_subagents(
+    lines: &mut Vec<Line>,
+    subagents: &[Session],
+    next_subagent: &mut usize,
+    next_message_time: Option<chrono::DateTime<chrono::Utc>>,
+    width: usize,
+) {
+    let t = theme();
+    while let Some(subagent) = subagents.get(*next_subagent) {
+        if next_message_time.is_some_and(|t| subagent.start_timestamp >= t) {
+            break;
+        }
+        let mut label = format!("   ⤷ Subagent  {}", subagent.title);
+        if let Some(duration) = format_duration(subagent.active_duration) {
+            label.push_str(&format!("  · {}", duration));
+        }
+        lines.push(Line::from(Span::styled(
+            truncate_chars(&label, width + 2),
+            Style::default().fg(t.dim_fg),
+        )));
+        *next_subagent += 1;
+    }
+}
+
+/// Add lines for the commits made before the next message (or all remaining ones
+/// after the last message)
+fn nest_commits(
+    lines: &mut Vec<Line>,
+    commits: &[Commit],
+    next_commit: &mut usize,
+    next_message_time: Option<chrono::DateTime<chrono::Utc>>,
+    width: usize,
+) {
+    let t = theme();
+    while let Some(commit) = commits.get(*next_commit) {
+        if next_message_time.is_some_and(|t| commit.timestamp >= t) {
+            break;
+        }
+        let subject = truncate_chars(&commit.subject, (width + 2).saturating_sub(13));
+        lines.push(Line::from(vec![
+            Span::styled(format!("   ● {} ", commit.short_hash()), Style::default().fg(t.accent_secondary)),
+            Span::styled(subject, Style::default().fg(t.dim_fg)),
+        ]));
+        *next_commit += 1;
+    }
+}
+
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     let t = theme();
     let keycap = Style::default().bg(t.keycap_bg);
     let label = Style::default();
     let dim = Style::default().fg(t.dim_fg);
 
-    let hints: Line = if let Some(ref msg) = app.status {
+    let hints: Line = if let Some(ref picker) = app.target_picker {
+        let mut spans = vec![Span::styled(" Open in ", keycap), Span::raw(" ")];
+        for (i, target) in picker.targets.iter().enumerate() {
+            let style = if i == picker.selected {
+                Style::default().fg(t.search_bg).bg(t.accent)
+            } else {
+                label
+            };
+            spans.push(Span::styled(format!(" {} {} ", i + 1, target.as_str()), style));
+        }
+        spans.extend([
+            Span::styled(" │ ", dim),
+            Span::styled(" ←→ ", keycap),
+            Span::styled(" choose ", label),
+            Span::styled(" │ ", dim),
+            Span::styled(" Enter ", keycap),
+            Span::styled(" open ", label),
+            Span::styled(" │ ", dim),
+            Span::styled(" Esc ", keycap),
+            Span::styled(" cancel", label),
+        ]);
+        Line::from(spans)
+    } else if let Some(ref find) = app.find {
+        let counter = if find.hits.is_empty() {
+            " no matches ".to_string()
+        } else {
+            format!(" {}/{} matches ", find.current + 1, find.match_count())
+        };
+        let mut spans = vec![
+            Span::styled(" Find ", keycap),
+            Span::raw(format!(" {}", find.query)),
+        ];
+        if find.editing {
+            spans.push(Span::styled(" ", Style::default().bg(t.accent)));
+        }
+        spans.extend([Span::styled(" │ ", dim), Span::styled(counter, Style::default().fg(t.match_fg))]);
+        let (key, action) = if find.editing { (" Enter ", " done ") } else { (" n/N ", " next/prev ") };
+        spans.extend([
+            Span::styled(" │ ", dim),
+            Span::styled(key, keycap),
+            Span::styled(action, label),
+            Span::styled(" │ ", dim),
+            Span::styled(" Esc ", keycap),
+            Span::styled(" close", label),
+        ]);
+        Line::from(spans)
+    } else if let Some(ref msg) = app.status {
         Line::from(Span::styled(msg, Style::default().fg(t.match_fg)))
     } else {
         let has_selection = !app.results.is_empty();
@@ -517,6 +764,14 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled(" copy ID ", label),
             ]);
         }
+        // Show related hint only if terminal is wide enough
+        if has_selection && app.related_to.is_none() && area.width > 130 {
+            spans.extend([
+                Span::styled(" │ ", dim),
+                Span::styled(" ^R ", keycap),
+                Span::styled(" related ", label),
+            ]);
+        }
         // Show Pg↑/↓ hint only if terminal is wide enough and there are messages
         if area.width > 90 && app.preview_message_count > 1 {
             spans.extend([
@@ -541,16 +796,22 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled(action, label),
             ]);
         }
+        let esc_action = if app.related_to.is_some() { " back" } else { " quit" };
         spans.extend([
             Span::styled(" │ ", dim),
             Span::styled(" Esc ", keycap),
-            Span::styled(" quit", label),
+            Span::styled(esc_action, label),
         ]);
         Line::from(spans)
     };
 
+    let subagents = match app.subagent_filter {
+        SubagentFilter::Include => "",
+        SubagentFilter::Exclude => " · no subagents",
+        SubagentFilter::Only => " · subagents only",
+    };
     let sessions_count = Span::styled(
-        format!(" {} sessions", app.total_sessions),
+        format!(" {} sessions{}", app.total_sessions, subagents),
         dim,
     );
 
@@ -717,37 +978,18 @@ fn highlight_with_spans(text: &str, spans: &[(usize, usize)]) -> Vec<Span<'stati
     result
 }
 
-/// Highlight query matches, returning owned Spans (for use with local variables)
-/// Splits query into words and highlights each word separately
-fn highlight_matches_owned(text: &str, query: &str) -> Vec<Span<'static>> {
-    let t = theme();
-    if query.is_empty() {
-        return vec![Span::raw(text.to_owned())];
-    }
-
-    let lower_text = text.to_lowercase();
-
-    // Split query into words and find all match positions
-    let query_words: Vec<&str> = query.split_whitespace().filter(|w| !w.is_empty()).collect();
-    if query_words.is_empty() {
-        return vec![Span::raw(text.to_owned())];
-    }
-
-    // Collect all match ranges (byte positions in original text)
+/// Find query matches in text, as merged byte ranges
+/// Splits query into words and matches each word separately (case-insensitive)
+fn match_ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+    // Collect all match ranges (byte positions in the text itself: lowercasing can
+    // change byte lengths, so ranges in a lowercased copy wouldn't line up)
     let mut matches: Vec<(usize, usize)> = Vec::new();
-    for word in &query_words {
-        let lower_word = word.to_lowercase();
-        for (match_start_lower, matched_str) in lower_text.match_indices(&lower_word) {
-            let char_offset = lower_text[..match_start_lower].chars().count();
-            let start = text.char_indices().nth(char_offset).map(|(i, _)| i).unwrap_or(text.len());
-
-            let match_char_len = matched_str.chars().count();
-            let end = text[start..].char_indices()
-                .nth(match_char_len)
-                .map(|(i, _)| start + i)
-                .unwrap_or(text.len());
-
-            matches.push((start, end));
+    for word in query.split_whitespace() {
+        let lower_word: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        for (start, _) in text.char_indices() {
+            if let Some(len) = match_len_ignoring_case(&text[start..], &lower_word) {
+                matches.push((start, start + len));
+            }
         }
     }
 
@@ -763,37 +1005,83 @@ fn highlight_matches_owned(text: &str, query: &str) -> Vec<Span<'static>> {
         }
         merged.push((start, end));
     }
+    merged
+}
 
-    // Build spans
-    let mut spans = Vec::new();
-    let mut last_end = 0;
-
-    for (start, end) in merged {
-        if start > last_end {
-            spans.push(Span::raw(text[last_end..start].to_owned()));
+/// Byte length of the start of `text` that matches a lowercased word, comparing
+/// lowercased characters (matches end on a character boundary of `text`)
+fn match_len_ignoring_case(text: &str, lower_word: &[char]) -> Option<usize> {
+    let mut matched = 0;
+    for (i, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if lower_word.get(matched) != Some(&lower) {
+                return None;
+            }
+            matched += 1;
+        }
+        if matched == lower_word.len() {
+            return Some(i + c.len_utf8());
         }
-        spans.push(Span::styled(
-            text[start..end].to_owned(),
-            Style::default()
-                .fg(t.match_fg)
-                .add_modifier(Modifier::BOLD),
-        ));
-        last_end = end;
     }
+    None
+}
 
-    if last_end < text.len() {
-        spans.push(Span::raw(text[last_end..].to_owned()));
+/// Style for a kind of rendered markdown text
+fn markdown_style(kind: SpanKind) -> Style {
+    let t = theme();
+    match kind {
+        SpanKind::Text => Style::default(),
+        SpanKind::Bold => Style::default().add_modifier(Modifier::BOLD),
+        SpanKind::Italic => Style::default().add_modifier(Modifier::ITALIC),
+        SpanKind::InlineCode | SpanKind::Code => Style::default().fg(t.code_fg),
+        SpanKind::Heading => Style::default().fg(t.heading_fg).add_modifier(Modifier::BOLD),
+        SpanKind::Quote => Style::default().fg(t.dim_fg).add_modifier(Modifier::ITALIC),
+        SpanKind::ListMarker => Style::default().fg(t.accent),
+        SpanKind::Rule => Style::default().fg(t.dim_fg),
+        SpanKind::CodeLabel => Style::default().fg(t.dim_fg).add_modifier(Modifier::ITALIC),
+        SpanKind::Keyword => Style::default().fg(t.code_keyword_fg),
+        SpanKind::String => Style::default().fg(t.code_string_fg),
+        SpanKind::Number => Style::default().fg(t.code_number_fg),
+        SpanKind::Comment => Style::default().fg(t.code_comment_fg).add_modifier(Modifier::ITALIC),
     }
+}
 
-    if spans.is_empty() {
-        spans.push(Span::raw(text.to_owned()));
+/// Spans for a rendered markdown line, with query matches (byte ranges in the
+/// line's text) highlighted on top of the markdown styles
+fn markdown_spans(line: &StyledLine, matches: &[(usize, usize)]) -> Vec<Span<'static>> {
+    let t = theme();
+    let mut spans = Vec::new();
+    let mut offset = 0;
+
+    for styled in &line.spans {
+        let style = markdown_style(styled.kind);
+        let span_end = offset + styled.text.len();
+
+        // Cut the span at every match boundary inside it
+        let mut cuts = vec![offset, span_end];
+        for &(start, end) in matches {
+            cuts.extend([start, end].into_iter().filter(|&b| b > offset && b < span_end));
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        for piece in cuts.windows(2) {
+            let (start, end) = (piece[0], piece[1]);
+            let text = styled.text[start - offset..end - offset].to_owned();
+            let matched = matches.iter().any(|&(m_start, m_end)| start >= m_start && end <= m_end);
+            let style = if matched {
+                style.fg(t.match_fg).add_modifier(Modifier::BOLD)
+            } else {
+                style
+            };
+            spans.push(Span::styled(text, style));
+        }
+        offset = span_end;
     }
 
     spans
 }
 
-
-
 /// Format a timestamp as a human-readable "time ago" string
 fn format_time_ago(timestamp: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
@@ -814,6 +1102,63 @@ fn format_time_ago(timestamp: chrono::DateTime<chrono::Utc>) -> String {
     }
 }
 
+/// Lines of a message's reasoning: just a header when collapsed, the full text
+/// (wrapped) when expanded
+fn reasoning_lines(reasoning: &str, expanded: bool, max_width: usize) -> Vec<String> {
+    let wrapped = wrap_text(reasoning, max_width.saturating_sub(2));
+    if !expanded {
+        let noun = if wrapped.len() == 1 { "line" } else { "lines" };
+        return vec![format!("▸ Thinking ({} {})", wrapped.len(), noun)];
+    }
+
+    let mut lines = vec!["▾ Thinking".to_string()];
+    lines.extend(wrapped.into_iter().map(|l| format!("  {}", l)));
+    lines.push(String::new());
+    lines
+}
+
+/// Lines of a compaction summary: a divider, followed by the summary (wrapped) when expanded
+fn compaction_lines(summary: &str, expanded: bool, max_width: usize) -> Vec<String> {
+    let wrapped = wrap_text(summary, max_width);
+    let label = if expanded {
+        " ▾ Conversation compacted ".to_string()
+    } else {
+        format!(" ▸ Conversation compacted ({} lines summarized) ", wrapped.len())
+    };
+    let rule_len = max_width.saturating_sub(label.chars().count() + 2);
+    let mut lines = vec![format!("──{}{}", label, "─".repeat(rule_len))];
+    if expanded {
+        lines.extend(wrapped);
+    }
+    lines
+}
+
+/// Truncate text to at most `max` chars, ending with "…" if shortened
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let truncated: String = text.chars().take(max - 1).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Format an active duration compactly ("45m", "2h 5m"), or None if under a minute
+pub fn format_duration(duration: chrono::Duration) -> Option<String> {
+    let minutes = duration.num_minutes();
+    if minutes < 1 {
+        None
+    } else if minutes < 60 {
+        Some(format!("{}m", minutes))
+    } else if minutes % 60 == 0 {
+        Some(format!("{}h", minutes / 60))
+    } else {
+        Some(format!("{}h {}m", minutes / 60, minutes % 60))
+    }
+}
+
 /// Select which line indices to show from a long message.
 /// Returns a Vec of (original_line_index, is_truncation_marker).
 /// The truncation marker uses usize::MAX as a sentinel value.
@@ -853,6 +1198,68 @@ fn select_lines_to_show(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("Fix login", 20), "Fix login");
+        assert_eq!(truncate_chars("Fix login bug", 8), "Fix log…");
+        assert_eq!(truncate_chars("Fix login bug", 0), "");
+    }
+
+    #[test]
+    fn test_reasoning_lines() {
+        assert_eq!(reasoning_lines("Check main.rs\nThen lib.rs", false, 40), vec!["▸ Thinking (2 lines)"]);
+        assert_eq!(
+            reasoning_lines("Check main.rs", true, 40),
+            vec!["▾ Thinking", "  Check main.rs", ""]
+        );
+    }
+
+    #[test]
+    fn test_compaction_lines() {
+        let collapsed = compaction_lines("Fixed the parser.\nNext: tests", false, 60);
+        assert_eq!(collapsed.len(), 1);
+        assert!(collapsed[0].starts_with("── ▸ Conversation compacted (2 lines summarized) ─"));
+        assert_eq!(collapsed[0].chars().count(), 60);
+
+        let expanded = compaction_lines("Fixed the parser.", true, 60);
+        assert_eq!(expanded[1], "Fixed the parser.");
+    }
+
+    #[test]
+    fn test_markdown_spans_highlight_matches_over_styles() {
+        let line = &markdown::render("**fix** the bug", 80)[0];
+        let matches = match_ranges(&line.text(), "fix bug");
+        let spans = markdown_spans(line, &matches);
+
+        let texts: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, vec!["fix", " the ", "bug"]);
+        assert_eq!(spans[0].style.fg, Some(theme().match_fg));
+        assert!(spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(spans[1].style.fg, None);
+        assert_eq!(spans[2].style.fg, Some(theme().match_fg));
+    }
+
+    #[test]
+    fn test_match_ranges_where_lowercasing_changes_lengths() {
+        // 'İ' lowercases to two chars, 'ẞ' to a shorter encoding
+        let text = "İİ ẞ Fix";
+        let ranges = match_ranges(text, "fix ß");
+        let matched: Vec<&str> = ranges.iter().map(|&(start, end)| &text[start..end]).collect();
+        assert_eq!(matched, vec!["ẞ", "Fix"]);
+
+        let line = &markdown::render(text, 80)[0];
+        let spans = markdown_spans(line, &match_ranges(&line.text(), "fix"));
+        assert_eq!(spans.last().unwrap().content, "Fix");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::seconds(30)), None);
+        assert_eq!(format_duration(chrono::Duration::minutes(45)), Some("45m".to_string()));
+        assert_eq!(format_duration(chrono::Duration::minutes(120)), Some("2h".to_string()));
+        assert_eq!(format_duration(chrono::Duration::minutes(125)), Some("2h 5m".to_string()));
+    }
+
     #[test]
     fn test_wrap_text_short_line() {
         let lines = wrap_text("Hello world", 80);